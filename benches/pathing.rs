@@ -0,0 +1,122 @@
+// Compares guard pathing against the full-map Dijkstra searches it replaced.
+//
+// Run with: cargo bench --bench pathing
+
+extern crate disguiser;
+extern crate rand;
+
+use disguiser::cell_grid::{INFINITE_COST, INVALID_REGION, Map, Random};
+use disguiser::coord::Coord;
+use disguiser::random_map;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+const NUM_SEEDS: u64 = 20;
+const LEVEL: usize = 9;
+
+fn main() {
+    let mut random = Random::seed_from_u64(1);
+    let maps: Vec<Map> = (0..NUM_SEEDS).map(|_| random_map::generate_map(&mut random, LEVEL)).collect();
+
+    let num_guards: usize = maps.iter().map(|map| map.guards.len()).sum();
+    println!("{} level-{} maps, {} guards", maps.len(), LEVEL + 1, num_guards);
+
+    // Patrol pathing: distance field to each guard's goal region
+
+    let region_full = time(|| {
+        for map in &maps {
+            for guard in &map.guards {
+                if guard.region_goal != INVALID_REGION {
+                    std::hint::black_box(map.compute_distances_to_region(guard.region_goal));
+                }
+            }
+        }
+    });
+
+    let region_cached = time(|| {
+        for map in &maps {
+            for guard in &map.guards {
+                if guard.region_goal != INVALID_REGION {
+                    std::hint::black_box(map.distances_to_region(guard.region_goal));
+                }
+            }
+        }
+    });
+
+    report("patrol region, full Dijkstra", region_full, num_guards);
+    report("patrol region, cached", region_cached, num_guards);
+
+    // Chase pathing: distance field to a point goal
+
+    let goals: Vec<Vec<Coord>> = maps.iter().map(|map| {
+        map.guards.iter().map(|_| random_walkable_pos(&mut random, map)).collect()
+    }).collect();
+
+    let point_full = time(|| {
+        for (map, goals) in maps.iter().zip(&goals) {
+            for goal in goals {
+                std::hint::black_box(map.compute_distances_to_position(*goal));
+            }
+        }
+    });
+
+    let point_astar = time(|| {
+        for (map, goals) in maps.iter().zip(&goals) {
+            for (guard, goal) in map.guards.iter().zip(goals) {
+                std::hint::black_box(map.compute_distances_to_position_near(*goal, guard.pos));
+            }
+        }
+    });
+
+    report("point goal, full Dijkstra", point_full, num_guards);
+    report("point goal, bounded A*", point_astar, num_guards);
+
+    // Check that A* agrees with Dijkstra wherever a guard could step next
+
+    for (map, goals) in maps.iter().zip(&goals) {
+        for (guard, goal) in map.guards.iter().zip(goals) {
+            let full = map.compute_distances_to_position(*goal);
+            let near = map.compute_distances_to_position_near(*goal, guard.pos);
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    let pos = guard.pos + Coord(dx, dy);
+                    if pos.0 < 0 || pos.1 < 0 || pos.0 >= map.cells.extents()[0] as i32 || pos.1 >= map.cells.extents()[1] as i32 {
+                        continue;
+                    }
+                    let p = [pos.0 as usize, pos.1 as usize];
+                    if near[p] != INFINITE_COST || map.cells[p].move_cost <= 10 {
+                        assert!(near[p] == full[p], "A* disagrees with Dijkstra next to a guard");
+                    }
+                }
+            }
+        }
+    }
+
+    println!("A* matches Dijkstra next to every guard");
+}
+
+fn random_walkable_pos(random: &mut Random, map: &Map) -> Coord {
+    let size_x = map.cells.extents()[0] as i32;
+    let size_y = map.cells.extents()[1] as i32;
+    loop {
+        let pos = Coord(random.gen_range(0..size_x), random.gen_range(0..size_y));
+        if map.cells[[pos.0 as usize, pos.1 as usize]].move_cost == 0 {
+            return pos;
+        }
+    }
+}
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    const ITERATIONS: u32 = 10;
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, elapsed: Duration, num_guards: usize) {
+    let per_guard = elapsed / num_guards as u32;
+    println!("{:32} {:>10.2?} per guard-turn", name, per_guard);
+}
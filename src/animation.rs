@@ -0,0 +1,142 @@
+use crate::coord::Coord;
+use crate::guard::Guard;
+use crate::speech_bubbles::{Popups, new_popups};
+
+// Motion and effects between turns. Turn logic stays discrete: at the start of
+// each turn the player's and guards' positions and the popups on screen are
+// recorded, and as frame time passes, drawing moves actors from the recorded
+// positions to their new ones and fades the old popups out and the new ones in.
+
+pub struct Animation {
+    turn_time: f32, // milliseconds since the latest turn
+    clock: f32, // milliseconds since the game started, for looping effects
+    player_pos_prev: Coord,
+    guard_pos_prev: Vec<Coord>,
+    popups_prev: Popups,
+}
+
+const MOVE_MS: f32 = 120.0;
+const POPUP_FADE_MS: f32 = 150.0;
+const DAMAGE_FLASH_MS: f32 = 300.0;
+const ANIMATION_MS: f32 = DAMAGE_FLASH_MS; // longest of the above
+
+// Ticks longer than this (after the page has been in the background, say) are
+// shortened, so that looping effects don't jump.
+const TICK_MS_MAX: f32 = 100.0;
+
+// Water ripples move in steps, so that the screen needn't be redrawn every frame
+// just for them.
+const RIPPLE_PERIOD_MS: f32 = 2400.0;
+const RIPPLE_STEP_MS: f32 = 100.0;
+
+pub fn new_animation(player_pos: Coord) -> Animation {
+    Animation {
+        turn_time: ANIMATION_MS,
+        clock: 0.0,
+        player_pos_prev: player_pos,
+        guard_pos_prev: Vec::new(),
+        popups_prev: new_popups(),
+    }
+}
+
+impl Animation {
+    pub fn begin_turn(&mut self, player_pos: Coord, guards: &[Guard], popups: &Popups) {
+        self.turn_time = 0.0;
+        self.player_pos_prev = player_pos;
+        self.guard_pos_prev = guards.iter().map(|guard| guard.pos).collect();
+        self.popups_prev = popups.clone();
+    }
+
+    // Skip to the end of the current turn's animations, e.g. when the map changes.
+
+    pub fn finish(&mut self) {
+        self.turn_time = ANIMATION_MS;
+        self.guard_pos_prev.clear();
+        self.popups_prev.clear();
+    }
+
+    pub fn advance(&mut self, dt_ms: f32) {
+        let dt_ms = dt_ms.clamp(0.0, TICK_MS_MAX);
+        self.turn_time += dt_ms;
+        self.clock = (self.clock + dt_ms) % RIPPLE_PERIOD_MS;
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.turn_time < ANIMATION_MS
+    }
+
+    pub fn player_pos_prev(&self) -> Coord {
+        self.player_pos_prev
+    }
+
+    pub fn popups_prev(&self) -> &Popups {
+        &self.popups_prev
+    }
+
+    // Position between from and to, according to how far along the moves are
+
+    pub fn tween(&self, from: Coord, to: Coord) -> Coord {
+        let t = self.move_fraction();
+        let d = to - from;
+        from + Coord((d.0 as f32 * t).round() as i32, (d.1 as f32 * t).round() as i32)
+    }
+
+    // Screen offsets, in pixels, from where actors are to where they are drawn
+
+    pub fn player_offset(&self, pos: Coord, tile_size: i32) -> Coord {
+        self.tween((self.player_pos_prev - pos) * tile_size, Coord(0, 0))
+    }
+
+    pub fn guard_offset(&self, i_guard: usize, pos: Coord, tile_size: i32) -> Coord {
+        match self.guard_pos_prev.get(i_guard) {
+            Some(&pos_prev) => self.tween((pos_prev - pos) * tile_size, Coord(0, 0)),
+            None => Coord(0, 0),
+        }
+    }
+
+    // Opacity of the popups from this turn, and of those from the turn before
+
+    pub fn popup_fade_in(&self) -> f32 {
+        (self.turn_time / POPUP_FADE_MS).min(1.0)
+    }
+
+    pub fn popup_fade_out(&self) -> f32 {
+        1.0 - self.popup_fade_in()
+    }
+
+    // Strength of the flash when the player is hurt, from 1 down to 0
+
+    pub fn damage_flash(&self) -> f32 {
+        (1.0 - self.turn_time / DAMAGE_FLASH_MS).max(0.0)
+    }
+
+    // Brightness of water, varying in waves across the map over time
+
+    pub fn water_ripple(&self, pos: Coord) -> f32 {
+        let time = self.ripple_step() as f32 * RIPPLE_STEP_MS;
+        let phase = time / RIPPLE_PERIOD_MS * std::f32::consts::TAU + pos.0 as f32 * 0.9 + pos.1 as f32 * 0.6;
+        0.85 + 0.15 * phase.sin()
+    }
+
+    // Changes whenever the water ripples should be redrawn
+
+    pub fn ripple_step(&self) -> u32 {
+        (self.clock / RIPPLE_STEP_MS) as u32
+    }
+
+    fn move_fraction(&self) -> f32 {
+        (self.turn_time / MOVE_MS).min(1.0)
+    }
+}
+
+// Colors are 0xAABBGGRR.
+
+pub fn fade_color(color: u32, opacity: f32) -> u32 {
+    let alpha = ((color >> 24) as f32 * opacity.clamp(0.0, 1.0)).round() as u32;
+    (alpha << 24) | (color & 0x00ffffff)
+}
+
+pub fn scale_brightness(color: u32, brightness: f32) -> u32 {
+    let scale = |shift: u32| -> u32 { ((((color >> shift) & 0xff) as f32 * brightness).round() as u32).min(0xff) << shift };
+    (color & 0xff000000) | scale(16) | scale(8) | scale(0)
+}
@@ -0,0 +1,76 @@
+use crate::coord::Coord;
+use crate::engine;
+
+// Sound effects. Game logic queues sounds at the world positions they come from;
+// the queue is played after each input event, with each sound's volume and pan
+// set by where it is relative to the player. Multi-step moves queue many sounds
+// at once, so repeats of a sound are played only once, at the loudest volume.
+
+// Sound IDs passed to js_play_sound(); the Javascript side has a matching table.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    Footstep = 0,
+    Creak = 1,
+    Splash = 2,
+    Shout = 3,
+    Damage = 4,
+    Coin = 5,
+    OutfitSwap = 6,
+    VoiceOuter = 7,
+    VoiceInner = 8,
+}
+
+struct SoundEvent {
+    sound: Sound,
+    pos: Coord,
+}
+
+pub struct Audio {
+    queued: Vec<SoundEvent>,
+}
+
+pub fn new_audio() -> Audio {
+    Audio { queued: Vec::new() }
+}
+
+// Sounds fade out completely at this distance, in cells, from the player.
+const HEARING_RADIUS: f32 = 16.0;
+
+// Sounds this many cells or more to one side of the player play entirely from that side.
+const PAN_DISTANCE: f32 = 8.0;
+
+impl Audio {
+    pub fn emit(&mut self, sound: Sound, pos: Coord) {
+        self.queued.push(SoundEvent { sound, pos });
+    }
+
+    pub fn play_queued(&mut self, pos_listener: Coord) {
+        let mut to_play: Vec<(Sound, f32, f32)> = Vec::new();
+
+        for event in self.queued.drain(..) {
+            let (volume, pan) = volume_and_pan(event.pos, pos_listener);
+            if volume <= 0.0 {
+                continue;
+            }
+
+            match to_play.iter_mut().find(|(sound, _, _)| *sound == event.sound) {
+                Some(playing) if playing.1 < volume => *playing = (event.sound, volume, pan),
+                Some(_) => {},
+                None => to_play.push((event.sound, volume, pan)),
+            }
+        }
+
+        for (sound, volume, pan) in to_play {
+            engine::play_sound(sound as i32, volume, pan);
+        }
+    }
+}
+
+fn volume_and_pan(pos_emitter: Coord, pos_listener: Coord) -> (f32, f32) {
+    let d = pos_emitter - pos_listener;
+    let dist = (d.length_squared() as f32).sqrt();
+    let volume = (1.0 - dist / HEARING_RADIUS).max(0.0);
+    let pan = (d.0 as f32 / PAN_DISTANCE).clamp(-1.0, 1.0);
+    (volume, pan)
+}
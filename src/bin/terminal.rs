@@ -0,0 +1,285 @@
+// Play the game in a text terminal, for instance over SSH, or to debug without a
+// browser. Build and run it with
+//
+//     cargo run --release --features terminal --bin disguiser-terminal [seed]
+//
+// Keys are passed to the game with the same codes the browser uses, so the key
+// bindings work as usual. Terminals that support the kitty keyboard protocol
+// report numpad keys separately; in others the numpad types digits, so use the
+// arrow keys or H J K L Y U B N to move, or rebind the keys with Esc.
+// Ctrl+Q quits.
+
+extern crate crossterm;
+extern crate disguiser;
+
+use crossterm::{cursor, event, execute, queue, style, terminal};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, KeyboardEnhancementFlags};
+use disguiser::engine;
+use disguiser::terminal::{TerminalCell, TerminalRenderer, new_terminal};
+use std::env;
+use std::io::{self, Write};
+use std::panic;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// How long to wait for input before advancing animations and redrawing
+
+const FRAME_TIME: Duration = Duration::from_millis(33);
+
+fn main() -> io::Result<()> {
+    let seed = env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or_else(seed_from_time);
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let keypad_reported = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if keypad_reported {
+        execute!(stdout, event::PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))?;
+    }
+
+    // Put the terminal back the way it was before reporting a panic
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal(&mut io::stdout(), keypad_reported);
+        default_hook(info);
+    }));
+
+    let result = run(&mut stdout, seed);
+    restore_terminal(&mut stdout, keypad_reported)?;
+    result
+}
+
+fn seed_from_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+}
+
+fn restore_terminal(stdout: &mut io::Stdout, keypad_reported: bool) -> io::Result<()> {
+    if keypad_reported {
+        execute!(stdout, event::PopKeyboardEnhancementFlags)?;
+    }
+    execute!(stdout, style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+fn run(stdout: &mut io::Stdout, seed: u64) -> io::Result<()> {
+    let (size_x, size_y) = terminal::size()?;
+    let mut game = new_terminal(seed, size_x as i32, size_y as i32);
+
+    let mut shown: Vec<TerminalCell> = Vec::new(); // what the terminal shows; empty after a resize
+    let mut time_prev = Instant::now();
+
+    loop {
+        draw(stdout, game.draw(), &mut shown)?;
+
+        if event::poll(FRAME_TIME)? {
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind != KeyEventKind::Release => {
+                    if key_event.code == KeyCode::Char('q') && key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                        return Ok(());
+                    }
+                    if let Some((key, ctrl_key_down, shift_key_down)) = key_from_event(&key_event) {
+                        game.key_down(key, ctrl_key_down, shift_key_down);
+                    }
+                },
+                Event::Resize(size_x, size_y) => {
+                    game.resize(size_x as i32, size_y as i32);
+                    shown.clear();
+                },
+                _ => {},
+            }
+        }
+
+        let time = Instant::now();
+        game.tick((time - time_prev).as_secs_f32() * 1000.0);
+        time_prev = time;
+    }
+}
+
+// Write the cells that differ from what the terminal shows
+
+fn draw(stdout: &mut io::Stdout, renderer: &TerminalRenderer, shown: &mut Vec<TerminalCell>) -> io::Result<()> {
+    let redraw_all = shown.len() != renderer.cells.len();
+    if redraw_all {
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+    }
+
+    let mut cursor_pos = None;
+    let mut colors = None;
+
+    for y in 0..renderer.size_y {
+        for x in 0..renderer.size_x {
+            let cell = renderer.cell(x, y);
+            if !redraw_all && shown[(y * renderer.size_x + x) as usize] == *cell {
+                continue;
+            }
+
+            if cursor_pos != Some((x, y)) {
+                queue!(stdout, cursor::MoveTo(x as u16, y as u16))?;
+            }
+
+            if colors != Some((cell.fg, cell.bg)) {
+                queue!(stdout, style::SetColors(style::Colors::new(terminal_color(cell.fg), terminal_color(cell.bg))))?;
+                colors = Some((cell.fg, cell.bg));
+            }
+
+            queue!(stdout, style::Print(cell.c))?;
+            cursor_pos = Some((x + 1, y));
+        }
+    }
+
+    shown.clear();
+    shown.extend_from_slice(&renderer.cells);
+    stdout.flush()
+}
+
+// Colors are 0xAABBGGRR
+
+fn terminal_color(color: u32) -> style::Color {
+    style::Color::Rgb {
+        r: color as u8,
+        g: (color >> 8) as u8,
+        b: (color >> 16) as u8,
+    }
+}
+
+// Translate a terminal key press into a key code and Ctrl and Shift states for
+// game::on_key_down(). Characters that need Shift on a US keyboard are passed as
+// the unshifted key with Shift held, as the browser would.
+
+fn key_from_event(key_event: &KeyEvent) -> Option<(i32, bool, bool)> {
+    let ctrl_key_down = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let shift_key_down = key_event.modifiers.contains(KeyModifiers::SHIFT);
+    let keypad = key_event.state.contains(KeyEventState::KEYPAD);
+
+    let (key, shifted) = match key_event.code {
+        KeyCode::Char(c) if keypad => (numpad_key(c)?, false),
+        KeyCode::Char(c) => char_key(c)?,
+        KeyCode::Enter if keypad => (engine::KEY_NUMPAD_ENTER, false),
+        KeyCode::Enter => (engine::KEY_ENTER, false),
+        KeyCode::Esc => (engine::KEY_ESCAPE, false),
+        KeyCode::Backspace => (engine::KEY_BACKSPACE, false),
+        KeyCode::Tab => (engine::KEY_TAB, false),
+        KeyCode::BackTab => (engine::KEY_TAB, true),
+        KeyCode::Left => (engine::KEY_LEFT, false),
+        KeyCode::Right => (engine::KEY_RIGHT, false),
+        KeyCode::Up => (engine::KEY_UP, false),
+        KeyCode::Down => (engine::KEY_DOWN, false),
+        KeyCode::Home => (engine::KEY_HOME, false),
+        KeyCode::End => (engine::KEY_END, false),
+        KeyCode::PageUp => (engine::KEY_PAGE_UP, false),
+        KeyCode::PageDown => (engine::KEY_PAGE_DOWN, false),
+        KeyCode::Insert => (engine::KEY_INSERT, false),
+        KeyCode::Delete => (engine::KEY_DELETE, false),
+        _ => return None,
+    };
+
+    Some((key, ctrl_key_down, shift_key_down || shifted))
+}
+
+fn char_key(c: char) -> Option<(i32, bool)> {
+    if c.is_ascii_alphabetic() {
+        return Some((engine::KEY_A + (c.to_ascii_uppercase() as i32 - 'A' as i32), c.is_ascii_uppercase()));
+    }
+
+    if let Some(digit) = c.to_digit(10) {
+        return Some((engine::KEY_0 + digit as i32, false));
+    }
+
+    let key = match c {
+        ' ' => (engine::KEY_SPACE, false),
+        ')' => (engine::KEY_0, true),
+        '!' => (engine::KEY_1, true),
+        '@' => (engine::KEY_2, true),
+        '#' => (engine::KEY_3, true),
+        '$' => (engine::KEY_4, true),
+        '%' => (engine::KEY_5, true),
+        '^' => (engine::KEY_6, true),
+        '&' => (engine::KEY_7, true),
+        '*' => (engine::KEY_8, true),
+        '(' => (engine::KEY_9, true),
+        ';' => (engine::KEY_SEMICOLON, false),
+        ':' => (engine::KEY_SEMICOLON, true),
+        '=' => (engine::KEY_EQUAL, false),
+        '+' => (engine::KEY_EQUAL, true),
+        ',' => (engine::KEY_COMMA, false),
+        '<' => (engine::KEY_COMMA, true),
+        '-' => (engine::KEY_MINUS, false),
+        '_' => (engine::KEY_MINUS, true),
+        '.' => (engine::KEY_PERIOD, false),
+        '>' => (engine::KEY_PERIOD, true),
+        '/' => (engine::KEY_SLASH, false),
+        '?' => (engine::KEY_SLASH, true),
+        '`' => (engine::KEY_BACKQUOTE, false),
+        '~' => (engine::KEY_BACKQUOTE, true),
+        '[' => (engine::KEY_BRACKET_LEFT, false),
+        '{' => (engine::KEY_BRACKET_LEFT, true),
+        '\\' => (engine::KEY_BACKSLASH, false),
+        '|' => (engine::KEY_BACKSLASH, true),
+        ']' => (engine::KEY_BRACKET_RIGHT, false),
+        '}' => (engine::KEY_BRACKET_RIGHT, true),
+        '\'' => (engine::KEY_QUOTE, false),
+        '"' => (engine::KEY_QUOTE, true),
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+fn numpad_key(c: char) -> Option<i32> {
+    if let Some(digit) = c.to_digit(10) {
+        return Some(engine::KEY_NUMPAD0 + digit as i32);
+    }
+
+    match c {
+        '*' => Some(engine::KEY_NUMPAD_MULTIPLY),
+        '+' => Some(engine::KEY_NUMPAD_ADD),
+        '-' => Some(engine::KEY_NUMPAD_SUBTRACT),
+        '.' => Some(engine::KEY_NUMPAD_DECIMAL),
+        '/' => Some(engine::KEY_NUMPAD_DIVIDE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventKind;
+
+    #[test]
+    fn letters_and_shifted_symbols() {
+        assert_eq!(char_key('h'), Some((engine::KEY_H, false)));
+        assert_eq!(char_key('H'), Some((engine::KEY_H, true)));
+        assert_eq!(char_key('5'), Some((engine::KEY_5, false)));
+        assert_eq!(char_key('?'), Some((engine::KEY_SLASH, true)));
+        assert_eq!(char_key('é'), None);
+    }
+
+    #[test]
+    fn numpad_keys() {
+        assert_eq!(numpad_key('5'), Some(engine::KEY_NUMPAD5));
+        assert_eq!(numpad_key('.'), Some(engine::KEY_NUMPAD_DECIMAL));
+        assert_eq!(numpad_key('a'), None);
+    }
+
+    #[test]
+    fn key_events() {
+        let key_event = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE);
+        assert_eq!(key_from_event(&key_event), Some((engine::KEY_SLASH, false, true)));
+
+        let key_event = KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(key_from_event(&key_event), Some((engine::KEY_LEFT, true, false)));
+
+        let key_event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE);
+        assert_eq!(key_from_event(&key_event), Some((engine::KEY_TAB, false, true)));
+
+        let key_event = KeyEvent::new_with_kind_and_state(KeyCode::Char('5'), KeyModifiers::NONE, KeyEventKind::Press, KeyEventState::KEYPAD);
+        assert_eq!(key_from_event(&key_event), Some((engine::KEY_NUMPAD5, false, false)));
+
+        let key_event = KeyEvent::new_with_kind_and_state(KeyCode::Enter, KeyModifiers::NONE, KeyEventKind::Press, KeyEventState::KEYPAD);
+        assert_eq!(key_from_event(&key_event), Some((engine::KEY_NUMPAD_ENTER, false, false)));
+
+        let key_event = KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE);
+        assert_eq!(key_from_event(&key_event), None);
+    }
+}
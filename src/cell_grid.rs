@@ -1,12 +1,10 @@
-use crate::color_preset;
 use crate::coord::Coord;
 use crate::guard;
 use multiarray::Array2D;
 use rand::prelude::SliceRandom;
-use std::cmp::min;
+use std::cmp::{min, max};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
-use std::collections::HashSet;
 use std::collections::VecDeque;
 
 pub type Random = rand_pcg::Pcg32;
@@ -48,6 +46,41 @@ pub enum CellType {
     DoorEW,
 }
 
+// All cell types, for looking them up by name in the tile definitions
+
+pub const CELL_TYPES: [CellType; 30] = [
+    CellType::GroundNormal,
+    CellType::GroundGrass,
+    CellType::GroundWater,
+    CellType::GroundMarble,
+    CellType::GroundWood,
+    CellType::GroundWoodCreaky,
+    CellType::Wall0000,
+    CellType::Wall0001,
+    CellType::Wall0010,
+    CellType::Wall0011,
+    CellType::Wall0100,
+    CellType::Wall0101,
+    CellType::Wall0110,
+    CellType::Wall0111,
+    CellType::Wall1000,
+    CellType::Wall1001,
+    CellType::Wall1010,
+    CellType::Wall1011,
+    CellType::Wall1100,
+    CellType::Wall1101,
+    CellType::Wall1110,
+    CellType::Wall1111,
+    CellType::OneWayWindowE,
+    CellType::OneWayWindowW,
+    CellType::OneWayWindowN,
+    CellType::OneWayWindowS,
+    CellType::PortcullisNS,
+    CellType::PortcullisEW,
+    CellType::DoorNS,
+    CellType::DoorEW,
+];
+
 pub const TURNS_UNDERWATER_MAX: usize = 7;
 
 pub const INVALID_REGION: usize = std::usize::MAX;
@@ -84,10 +117,22 @@ pub struct Map {
     pub cells: CellGrid,
     pub patrol_regions: Vec<PatrolRegion>,
     pub patrol_routes: Vec<(usize, usize)>,
+    pub region_distance_fields: Vec<Array2D<usize>>, // cached; see update_region_distance_fields()
     pub items: Vec<Item>,
     pub guards: Vec<guard::Guard>,
     pub pos_start: Coord,
     pub total_loot: usize,
+    pub earshot: Earshot,
+}
+
+// Reusable scratch space for sound propagation, so making noise doesn't allocate.
+
+pub struct Earshot {
+    pos_emitter: Coord, // source of the most recent query
+    radius: i32,
+    generation: u32, // stamp of the most recent query
+    visited: Array2D<u32>, // stamp of the query that last reached each cell
+    to_visit: VecDeque<Coord>,
 }
 
 pub struct Item {
@@ -108,6 +153,21 @@ pub enum ItemKind {
     Outfit(Option<guard::GuardKind>),
 }
 
+// All item kinds, for looking them up by name in the tile definitions. Outfits
+// share one definition regardless of guard kind.
+
+pub const ITEM_KINDS: [ItemKind; 9] = [
+    ItemKind::Chair,
+    ItemKind::Table,
+    ItemKind::Bush,
+    ItemKind::Coin,
+    ItemKind::DoorNS,
+    ItemKind::DoorEW,
+    ItemKind::PortcullisNS,
+    ItemKind::PortcullisEW,
+    ItemKind::Outfit(None),
+];
+
 pub struct Player {
     pub pos: Coord,
     pub dir: Coord,
@@ -121,104 +181,6 @@ pub struct Player {
     pub turns_remaining_underwater: usize,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Tile {
-    pub glyph: u32,
-    pub color: u32,
-    pub blocks_player: bool,
-    pub blocks_player_sight: bool,
-    pub blocks_sight: bool,
-    pub blocks_sound: bool,
-    pub ignores_lighting: bool,
-}
-
-pub fn tile_def(tile_type: CellType) -> &'static Tile {
-    match tile_type {
-        CellType::GroundNormal     => &Tile { glyph: 128, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-        CellType::GroundGrass      => &Tile { glyph: 132, color: color_preset::DARK_GREEN, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-        CellType::GroundWater      => &Tile { glyph: 134, color: color_preset::LIGHT_BLUE, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-        CellType::GroundMarble     => &Tile { glyph: 136, color: color_preset::DARK_CYAN, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-        CellType::GroundWood       => &Tile { glyph: 138, color: color_preset::DARK_BROWN, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-        CellType::GroundWoodCreaky => &Tile { glyph: 138, color: color_preset::DARK_BROWN, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: false },
-
-                  //  NSEW
-        CellType::Wall0000 => &Tile { glyph: 176, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: false, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0001 => &Tile { glyph: 177, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0010 => &Tile { glyph: 177, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0011 => &Tile { glyph: 177, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0100 => &Tile { glyph: 178, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0101 => &Tile { glyph: 179, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0110 => &Tile { glyph: 182, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall0111 => &Tile { glyph: 185, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1000 => &Tile { glyph: 178, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1001 => &Tile { glyph: 180, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1010 => &Tile { glyph: 181, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1011 => &Tile { glyph: 184, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1100 => &Tile { glyph: 178, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1101 => &Tile { glyph: 186, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1110 => &Tile { glyph: 183, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-        CellType::Wall1111 => &Tile { glyph: 187, color: color_preset::LIGHT_GRAY, blocks_player: true, blocks_player_sight: true, blocks_sight: true, blocks_sound: true, ignores_lighting: true },
-
-        CellType::OneWayWindowE => &Tile { glyph: 196, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: true, blocks_sound: false, ignores_lighting: true },
-        CellType::OneWayWindowW => &Tile { glyph: 197, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: true, blocks_sound: false, ignores_lighting: true },
-        CellType::OneWayWindowN => &Tile { glyph: 198, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: true, blocks_sound: false, ignores_lighting: true },
-        CellType::OneWayWindowS => &Tile { glyph: 199, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: true, blocks_sound: false, ignores_lighting: true },
-        CellType::PortcullisNS  => &Tile { glyph: 128, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: true },
-        CellType::PortcullisEW  => &Tile { glyph: 128, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: true },
-        CellType::DoorNS        => &Tile { glyph: 189, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: true },
-        CellType::DoorEW        => &Tile { glyph: 188, color: color_preset::LIGHT_GRAY, blocks_player: false, blocks_player_sight: false, blocks_sight: false, blocks_sound: false, ignores_lighting: true },
-    }
-}
-
-pub fn guard_move_cost_for_tile_type(tile_type: CellType) -> usize {
-    match tile_type {
-        CellType::GroundNormal     => 0,
-        CellType::GroundGrass      => 0,
-        CellType::GroundWater      => 4096,
-        CellType::GroundMarble     => 0,
-        CellType::GroundWood       => 0,
-        CellType::GroundWoodCreaky => 0,
-        CellType::Wall0000         => INFINITE_COST,
-        CellType::Wall0001         => INFINITE_COST,
-        CellType::Wall0010         => INFINITE_COST,
-        CellType::Wall0011         => INFINITE_COST,
-        CellType::Wall0100         => INFINITE_COST,
-        CellType::Wall0101         => INFINITE_COST,
-        CellType::Wall0110         => INFINITE_COST,
-        CellType::Wall0111         => INFINITE_COST,
-        CellType::Wall1000         => INFINITE_COST,
-        CellType::Wall1001         => INFINITE_COST,
-        CellType::Wall1010         => INFINITE_COST,
-        CellType::Wall1011         => INFINITE_COST,
-        CellType::Wall1100         => INFINITE_COST,
-        CellType::Wall1101         => INFINITE_COST,
-        CellType::Wall1110         => INFINITE_COST,
-        CellType::Wall1111         => INFINITE_COST,
-        CellType::OneWayWindowE    => INFINITE_COST,
-        CellType::OneWayWindowW    => INFINITE_COST,
-        CellType::OneWayWindowN    => INFINITE_COST,
-        CellType::OneWayWindowS    => INFINITE_COST,
-        CellType::PortcullisNS     => 0,
-        CellType::PortcullisEW     => 0,
-        CellType::DoorNS           => 0,
-        CellType::DoorEW           => 0,
-    }
-}
-
-pub fn guard_move_cost_for_item_kind(kind: ItemKind) -> usize {
-    match kind {
-        ItemKind::Chair => 4,
-        ItemKind::Table => 10,
-        ItemKind::Bush => 10,
-        ItemKind::Coin => 0,
-        ItemKind::DoorNS => 0,
-        ItemKind::DoorEW => 0,
-        ItemKind::PortcullisNS => 0,
-        ItemKind::PortcullisEW => 0,
-        ItemKind::Outfit(_) => 10,
-    }
-}
-
 pub fn make_player(pos: Coord) -> Player {
     let health = 5;
     Player {
@@ -285,7 +247,7 @@ impl Player {
     }
 }
 
-const ADJACENT_MOVES: [(usize, Coord); 8] = [
+pub(crate) const ADJACENT_MOVES: [(usize, Coord); 8] = [
     (2, Coord(1, 0)),
     (2, Coord(-1, 0)),
     (2, Coord(0, 1)),
@@ -296,6 +258,27 @@ const ADJACENT_MOVES: [(usize, Coord); 8] = [
     (3, Coord(1, 1)),
 ];
 
+// Entry in the priority queue for the searches over the map; the heap pops the
+// lowest distance first.
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub(crate) struct State {
+    pub dist: usize,
+    pub pos: Coord,
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &State) -> Ordering {
+        other.dist.cmp(&self.dist)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 const SOUND_NEIGHBORS: [Coord; 4] = [
     Coord(-1, 0),
     Coord(1, 0),
@@ -578,25 +561,6 @@ pub fn guard_move_cost(&self, pos_old: Coord, pos_new: Coord) -> usize {
 }
 
 pub fn closest_region(&self, pos: Coord) -> usize {
-
-    #[derive(Copy, Clone, Eq, PartialEq)]
-    struct State {
-        dist: usize,
-        pos: Coord,
-    }
-
-    impl Ord for State {
-        fn cmp(&self, other: &State) -> Ordering {
-            other.dist.cmp(&self.dist)
-        }
-    }
-
-    impl PartialOrd for State {
-        fn partial_cmp(&self, other: &State) -> Option<Ordering> {
-            Some(self.cmp(other))
-        }
-    }
-
     let mut heap = BinaryHeap::with_capacity(self.cells.extents()[0] * self.cells.extents()[1]);
     let mut dist_field: Array2D<usize> = Array2D::new([self.cells.extents()[0], self.cells.extents()[1]], INFINITE_COST);
 
@@ -641,6 +605,21 @@ pub fn closest_region(&self, pos: Coord) -> usize {
     INVALID_REGION
 }
 
+// Guards path to their patrol regions every turn, so the distance fields for the
+// regions are computed up front. They must be recomputed whenever cells' move costs
+// change.
+
+pub fn update_region_distance_fields(&mut self) {
+    self.region_distance_fields = (0..self.patrol_regions.len())
+        .map(|i_region| self.compute_distances_to_region(i_region))
+        .collect();
+}
+
+pub fn distances_to_region(&self, i_region_goal: usize) -> &Array2D<usize> {
+    assert!(self.region_distance_fields.len() == self.patrol_regions.len());
+    &self.region_distance_fields[i_region_goal]
+}
+
 pub fn compute_distances_to_region(&self, i_region_goal: usize) -> Array2D<usize> {
     assert!(i_region_goal < self.patrol_regions.len());
 
@@ -669,17 +648,33 @@ pub fn compute_distances_to_position(&self, pos_goal: Coord) -> Array2D<usize> {
     self.compute_distance_field(&[(0, pos_goal)])
 }
 
-pub fn compute_distance_field(&self, initial_distances: &[(usize, Coord)]) -> Array2D<usize> {
+// A* search outward from pos_goal toward pos_from. Only the cells needed to pick
+// pos_from's next step get their exact distances; cells that weren't reached are
+// left at INFINITE_COST.
+
+pub fn compute_distances_to_position_near(&self, pos_goal: Coord, pos_from: Coord) -> Array2D<usize> {
+    assert!(pos_goal.0 >= 0);
+    assert!(pos_goal.1 >= 0);
+    assert!(pos_goal.0 < self.cells.extents()[0] as i32);
+    assert!(pos_goal.1 < self.cells.extents()[1] as i32);
+
+    // After reaching pos_from, keep searching until every neighbor of pos_from
+    // that a guard might step onto has been finalized. A neighbor costs at most
+    // a diagonal move plus the largest non-water cell cost more than pos_from,
+    // and its heuristic is at most one diagonal move.
+
+    const NEIGHBOR_SLACK: usize = 3 + 10 + 3;
 
     #[derive(Copy, Clone, Eq, PartialEq)]
     struct State {
+        estimate: usize,
         dist: usize,
         pos: Coord,
     }
 
     impl Ord for State {
         fn cmp(&self, other: &State) -> Ordering {
-            other.dist.cmp(&self.dist)
+            other.estimate.cmp(&self.estimate)
         }
     }
 
@@ -689,6 +684,63 @@ pub fn compute_distance_field(&self, initial_distances: &[(usize, Coord)]) -> Ar
         }
     }
 
+    // Octile distance with straight moves costing 2 and diagonal moves 3
+
+    let heuristic = |pos: Coord| {
+        let dx = (pos.0 - pos_from.0).unsigned_abs() as usize;
+        let dy = (pos.1 - pos_from.1).unsigned_abs() as usize;
+        2 * max(dx, dy) + min(dx, dy)
+    };
+
+    let mut heap = BinaryHeap::new();
+    let mut dist_field: Array2D<usize> = Array2D::new([self.cells.extents()[0], self.cells.extents()[1]], INFINITE_COST);
+
+    let size_x = self.cells.extents()[0] as i32;
+    let size_y = self.cells.extents()[1] as i32;
+
+    let mut estimate_limit = INFINITE_COST;
+
+    heap.push(State{estimate: heuristic(pos_goal), dist: 0, pos: pos_goal});
+
+    while let Some(State {estimate, dist, pos}) = heap.pop() {
+        if estimate > estimate_limit {
+            break;
+        }
+
+        let p = [pos.0 as usize, pos.1 as usize];
+        if dist >= dist_field[p] {
+            continue;
+        }
+
+        dist_field[p] = dist;
+
+        if pos == pos_from {
+            estimate_limit = dist + NEIGHBOR_SLACK;
+        }
+
+        for (move_dir_cost, dir) in &ADJACENT_MOVES {
+            let pos_new = pos + *dir;
+            if pos_new.0 < 0 || pos_new.1 < 0 || pos_new.0 >= size_x || pos_new.1 >= size_y {
+                continue;
+            }
+
+            let move_cost = self.guard_move_cost(pos, pos_new);
+            if move_cost == INFINITE_COST {
+                continue;
+            }
+
+            let dist_new = dist + move_cost + move_dir_cost;
+
+            if dist_new < dist_field[[pos_new.0 as usize, pos_new.1 as usize]] {
+                heap.push(State{estimate: dist_new + heuristic(pos_new), dist: dist_new, pos: pos_new});
+            }
+        }
+    }
+
+    dist_field
+}
+
+pub fn compute_distance_field(&self, initial_distances: &[(usize, Coord)]) -> Array2D<usize> {
     let mut heap = BinaryHeap::with_capacity(self.cells.extents()[0] * self.cells.extents()[1]);
     let mut dist_field: Array2D<usize> = Array2D::new([self.cells.extents()[0], self.cells.extents()[1]], INFINITE_COST);
 
@@ -741,32 +793,38 @@ pub fn hides_player(&self, x: i32, y: i32) -> bool {
     self.cells[[x as usize, y as usize]].hides_player
 }
 
-pub fn coords_in_earshot(&self, emitter_pos: Coord, radius: i32) -> HashSet<Coord> {
-    // Flood-fill from the emitter position.
-
-    let capacity = self.cells.extents()[0] * self.cells.extents()[1];
-    let mut coords_visited: HashSet<Coord> = HashSet::with_capacity(capacity);
-    let mut coords_to_visit: VecDeque<Coord> = VecDeque::with_capacity(capacity);
+// Flood-fill from the emitter position, marking all the cells the sound reaches.
+// Afterward, in_earshot() tells whether a given cell heard it.
 
-    coords_to_visit.push_back(emitter_pos);
+pub fn compute_earshot(&mut self, emitter_pos: Coord, radius: i32) {
+    let size_x = self.cells.extents()[0] as i32;
+    let size_y = self.cells.extents()[1] as i32;
 
-    while let Some(pos) = coords_to_visit.pop_front() {
+    let earshot = &mut self.earshot;
+    earshot.start_query();
+    earshot.pos_emitter = emitter_pos;
+    earshot.radius = radius;
 
-        coords_visited.insert(pos);
+    let generation = earshot.generation;
+    earshot.visited[[emitter_pos.0 as usize, emitter_pos.1 as usize]] = generation;
+    earshot.to_visit.push_back(emitter_pos);
 
+    while let Some(pos) = earshot.to_visit.pop_front() {
         for dir in &SOUND_NEIGHBORS {
             let new_pos = pos + *dir;
 
             // Skip positions that are off the map.
 
-            if new_pos.0 < 0 || new_pos.0 >= self.cells.extents()[0] as i32 ||
-               new_pos.1 < 0 || new_pos.1 >= self.cells.extents()[1] as i32 {
+            if new_pos.0 < 0 || new_pos.0 >= size_x ||
+               new_pos.1 < 0 || new_pos.1 >= size_y {
                 continue;
             }
 
+            let p = [new_pos.0 as usize, new_pos.1 as usize];
+
             // Skip neighbors that have already been visited.
 
-            if coords_visited.contains(&new_pos) {
+            if earshot.visited[p] == generation {
                 continue;
             }
 
@@ -780,20 +838,76 @@ pub fn coords_in_earshot(&self, emitter_pos: Coord, radius: i32) -> HashSet<Coor
 
             // Skip neighbors that don't transmit sound
 
-            if self.cells[[new_pos.0 as usize, new_pos.1 as usize]].blocks_sound {
+            if self.cells[p].blocks_sound {
                 continue;
             }
 
-            coords_to_visit.push_back(new_pos);
+            earshot.visited[p] = generation;
+            earshot.to_visit.push_back(new_pos);
         }
     }
+}
 
-    coords_visited
+pub fn in_earshot(&self, pos: Coord) -> bool {
+    self.earshot.contains(pos)
 }
 
-pub fn guards_in_earshot(&mut self, emitter_pos: Coord, radius: i32) -> Vec<&mut guard::Guard> {
-    let coords_in_earshot = self.coords_in_earshot(emitter_pos, radius);
-    self.guards.iter_mut().filter(|guard| coords_in_earshot.contains(&guard.pos)).collect()
+// List the cells reached by the most recent compute_earshot().
+
+pub fn coords_in_earshot(&self) -> Vec<Coord> {
+    let earshot = &self.earshot;
+    let r = (earshot.radius as f32).sqrt() as i32 + 1;
+    let pos_min = Coord(max(0, earshot.pos_emitter.0 - r), max(0, earshot.pos_emitter.1 - r));
+    let pos_max = Coord(min(self.cells.extents()[0] as i32, earshot.pos_emitter.0 + r + 1), min(self.cells.extents()[1] as i32, earshot.pos_emitter.1 + r + 1));
+
+    let mut coords = Vec::new();
+    for x in pos_min.0 .. pos_max.0 {
+        for y in pos_min.1 .. pos_max.1 {
+            if earshot.contains(Coord(x, y)) {
+                coords.push(Coord(x, y));
+            }
+        }
+    }
+    coords
+}
+
+pub fn guards_in_earshot(&mut self, emitter_pos: Coord, radius: i32) -> impl Iterator<Item = &mut guard::Guard> {
+    self.compute_earshot(emitter_pos, radius);
+    let earshot = &self.earshot;
+    self.guards.iter_mut().filter(move |guard| earshot.contains(guard.pos))
+}
+
+}
+
+pub fn new_earshot(size_x: usize, size_y: usize) -> Earshot {
+    Earshot {
+        pos_emitter: Coord(0, 0),
+        radius: 0,
+        generation: 1, // unvisited cells are stamped 0, so nothing is in earshot before the first query
+        visited: Array2D::new([size_x, size_y], 0),
+        to_visit: VecDeque::new(),
+    }
 }
 
+impl Earshot {
+    fn contains(&self, pos: Coord) -> bool {
+        self.visited[[pos.0 as usize, pos.1 as usize]] == self.generation
+    }
+
+    fn start_query(&mut self) {
+        // Bumping the generation clears the visit marks; they only need to be
+        // reset for real when the counter wraps around.
+
+        if self.generation == u32::MAX {
+            for x in 0..self.visited.extents()[0] {
+                for y in 0..self.visited.extents()[1] {
+                    self.visited[[x, y]] = 0;
+                }
+            }
+            self.generation = 0;
+        }
+
+        self.generation += 1;
+        self.to_visit.clear();
+    }
 }
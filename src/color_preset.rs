@@ -15,3 +15,25 @@ pub const LIGHT_RED: u32 = 0xff5454fe;
 pub const LIGHT_MAGENTA: u32 = 0xfffe54fe;
 pub const LIGHT_YELLOW: u32 = 0xff54fefe;
 pub const WHITE: u32 = 0xfffefefe;
+
+pub fn from_name(name: &str) -> Option<u32> {
+    match name {
+        "BLACK" => Some(BLACK),
+        "DARK_BLUE" => Some(DARK_BLUE),
+        "DARK_GREEN" => Some(DARK_GREEN),
+        "DARK_CYAN" => Some(DARK_CYAN),
+        "DARK_RED" => Some(DARK_RED),
+        "DARK_MAGENTA" => Some(DARK_MAGENTA),
+        "DARK_BROWN" => Some(DARK_BROWN),
+        "LIGHT_GRAY" => Some(LIGHT_GRAY),
+        "DARK_GRAY" => Some(DARK_GRAY),
+        "LIGHT_BLUE" => Some(LIGHT_BLUE),
+        "LIGHT_GREEN" => Some(LIGHT_GREEN),
+        "LIGHT_CYAN" => Some(LIGHT_CYAN),
+        "LIGHT_RED" => Some(LIGHT_RED),
+        "LIGHT_MAGENTA" => Some(LIGHT_MAGENTA),
+        "LIGHT_YELLOW" => Some(LIGHT_YELLOW),
+        "WHITE" => Some(WHITE),
+        _ => None,
+    }
+}
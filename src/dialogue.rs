@@ -0,0 +1,221 @@
+use crate::cell_grid::Random;
+use crate::guard::GuardKind;
+use crate::localization::{self, Language, LineSet};
+
+use rand::Rng;
+use std::collections::VecDeque;
+
+// What guards say. Each line set in the localization tables is a list of lines
+// with conditions on when they can be said; a guard picks randomly among the
+// lines whose conditions hold, weighted, and avoids lines said recently.
+//
+// Exchanges are short back-and-forth conversations between two patrolling
+// guards who meet. The guards take turns saying the lines, starting with the
+// one whose situation the conditions were checked against.
+
+// The guard's situation, for checking line conditions against
+
+pub struct Context {
+    pub guard_kind: GuardKind,
+    pub wary: bool, // has the guard chased the thief on this level?
+    pub alert: bool, // is the guard investigating, or has it seen or heard the thief in the last few turns?
+    pub disguise: Disguise,
+    pub turn: usize,
+    pub near_guards: bool,
+}
+
+// How the player is dressed, relative to the guard
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Disguise {
+    Plain,
+    AsSameKind,
+    AsOtherKind,
+}
+
+// Conditions are built up from ANY, e.g. ANY.guard_kind(GuardKind::Inner).wary(true)
+
+#[derive(Clone, Copy)]
+pub struct Condition {
+    guard_kind: Option<GuardKind>,
+    wary: Option<bool>,
+    alert: Option<bool>,
+    disguise: Option<Disguise>,
+    turn_min: usize,
+    near_guards: Option<bool>,
+}
+
+pub const ANY: Condition = Condition {
+    guard_kind: None,
+    wary: None,
+    alert: None,
+    disguise: None,
+    turn_min: 0,
+    near_guards: None,
+};
+
+impl Condition {
+    pub const fn guard_kind(self, guard_kind: GuardKind) -> Condition {
+        Condition { guard_kind: Some(guard_kind), ..self }
+    }
+
+    pub const fn wary(self, wary: bool) -> Condition {
+        Condition { wary: Some(wary), ..self }
+    }
+
+    pub const fn alert(self, alert: bool) -> Condition {
+        Condition { alert: Some(alert), ..self }
+    }
+
+    pub const fn disguise(self, disguise: Disguise) -> Condition {
+        Condition { disguise: Some(disguise), ..self }
+    }
+
+    pub const fn after_turn(self, turn: usize) -> Condition {
+        Condition { turn_min: turn, ..self }
+    }
+
+    pub const fn near_guards(self, near_guards: bool) -> Condition {
+        Condition { near_guards: Some(near_guards), ..self }
+    }
+
+    fn holds(&self, context: &Context) -> bool {
+        self.guard_kind.is_none_or(|guard_kind| guard_kind == context.guard_kind) &&
+        self.wary.is_none_or(|wary| wary == context.wary) &&
+        self.alert.is_none_or(|alert| alert == context.alert) &&
+        self.disguise.is_none_or(|disguise| disguise == context.disguise) &&
+        context.turn >= self.turn_min &&
+        self.near_guards.is_none_or(|near_guards| near_guards == context.near_guards)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Line {
+    pub text: &'static str,
+    when: Condition,
+    weight: u32,
+}
+
+pub const fn line(text: &'static str) -> Line {
+    Line { text, when: ANY, weight: 1 }
+}
+
+impl Line {
+    pub const fn when(self, when: Condition) -> Line {
+        Line { when, ..self }
+    }
+
+    pub const fn weight(self, weight: u32) -> Line {
+        Line { weight, ..self }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Exchange {
+    pub lines: &'static [&'static str],
+    when: Condition,
+    weight: u32,
+}
+
+pub const fn exchange(lines: &'static [&'static str]) -> Exchange {
+    Exchange { lines, when: ANY, weight: 1 }
+}
+
+impl Exchange {
+    pub const fn when(self, when: Condition) -> Exchange {
+        Exchange { when, ..self }
+    }
+
+    pub const fn weight(self, weight: u32) -> Exchange {
+        Exchange { weight, ..self }
+    }
+}
+
+// An exchange in progress between two guards, identified by index in the map.
+// Only one conversation happens at a time.
+
+pub struct Conversation {
+    pub speakers: [usize; 2],
+    pub lines: &'static [&'static str],
+    pub line_index: usize, // next line to say; speakers alternate
+}
+
+pub struct Dialogue {
+    language: Language,
+    recent: VecDeque<&'static str>, // most recent last
+    pub conversation: Option<Conversation>,
+    pub turn_next_conversation: usize, // no new conversations start before this turn
+}
+
+const RECENT_LINES_MAX: usize = 8;
+
+pub fn new_dialogue(language: Language) -> Dialogue {
+    Dialogue {
+        language,
+        recent: VecDeque::with_capacity(RECENT_LINES_MAX),
+        conversation: None,
+        turn_next_conversation: 0,
+    }
+}
+
+impl Dialogue {
+    // A conversation in progress was chosen from the old language's table, so it
+    // is dropped.
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+        self.conversation = None;
+    }
+
+    // Conversations refer to guards by index, so must be dropped when the map changes.
+
+    pub fn new_level(&mut self) {
+        self.conversation = None;
+        self.turn_next_conversation = 0;
+    }
+
+    pub fn line(&mut self, random: &mut Random, line_set: LineSet, context: &Context) -> &'static str {
+        let lines = localization::lines(self.language, line_set);
+        let recent = &self.recent;
+
+        let mut candidates: Vec<&Line> = lines.iter().filter(|line| line.when.holds(context) && !recent.contains(&line.text)).collect();
+        if candidates.is_empty() {
+            candidates = lines.iter().filter(|line| line.when.holds(context)).collect();
+        }
+        if candidates.is_empty() {
+            candidates = lines.iter().collect();
+        }
+
+        let text = choose_weighted(random, &candidates, |line| line.weight).map_or(lines[0].text, |line| line.text);
+
+        if self.recent.len() >= RECENT_LINES_MAX {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(text);
+
+        text
+    }
+
+    pub fn choose_exchange(&self, random: &mut Random, context: &Context) -> Option<&'static [&'static str]> {
+        let candidates: Vec<&Exchange> = localization::exchanges(self.language).iter().filter(|exchange| exchange.when.holds(context)).collect();
+        choose_weighted(random, &candidates, |exchange| exchange.weight).map(|exchange| exchange.lines)
+    }
+}
+
+fn choose_weighted<'a, T, F>(random: &mut Random, candidates: &[&'a T], weight: F) -> Option<&'a T> where F: Fn(&T) -> u32 {
+    let weight_total: u32 = candidates.iter().map(|candidate| weight(candidate)).sum();
+    if weight_total == 0 {
+        return None;
+    }
+
+    let mut r = random.gen_range(0..weight_total);
+    for candidate in candidates {
+        let w = weight(candidate);
+        if r < w {
+            return Some(candidate);
+        }
+        r -= w;
+    }
+
+    None
+}
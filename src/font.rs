@@ -0,0 +1,153 @@
+use crate::fontdata::{self, Glyph, GLYPH};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// Bitmap fonts. The font in fontdata.rs is built in; others can be loaded at
+// runtime from BMFont text descriptors (.fnt files), as written by tools such as
+// AngelCode's Bitmap Font Generator or Hiero. Glyph coordinates are pixels in the
+// font texture, which is a single image.
+
+pub struct Font {
+    pub line_height: i32,
+    glyphs: HashMap<usize, Glyph>,
+    kerning: HashMap<(usize, usize), i32>, // (first, second) character ids to x adjustment
+}
+
+pub fn new_builtin_font() -> Font {
+    Font {
+        line_height: fontdata::LINE_HEIGHT,
+        glyphs: GLYPH.iter().map(|glyph| (glyph.id, *glyph)).collect(),
+        kerning: HashMap::new(),
+    }
+}
+
+impl Font {
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&(c as usize))
+    }
+
+    // Horizontal adjustment to the position of second when it follows first
+
+    pub fn kerning(&self, first: char, second: char) -> i32 {
+        self.kerning.get(&(first as usize, second as usize)).copied().unwrap_or(0)
+    }
+}
+
+// The font used for all text drawing. The game is single-threaded, so this is
+// effectively a global.
+
+thread_local! {
+    static FONT: RefCell<Font> = RefCell::new(new_builtin_font());
+}
+
+pub fn with_font<F, R>(f: F) -> R where F: FnOnce(&Font) -> R {
+    FONT.with(|font| f(&font.borrow()))
+}
+
+pub fn set_font(font: Font) {
+    FONT.with(|current| *current.borrow_mut() = font);
+}
+
+// Parse a BMFont text descriptor. Tags other than common, char and kerning are
+// ignored, as are attributes that don't affect drawing.
+
+pub fn parse_bmfont(descriptor: &str) -> Result<Font, String> {
+    let mut line_height = None;
+    let mut glyphs = HashMap::new();
+    let mut kerning = HashMap::new();
+
+    for (line_index, line) in descriptor.lines().enumerate() {
+        let line_number = line_index + 1;
+        let (tag, attributes) = match parse_line(line) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+
+        let attribute = |key: &str| -> Result<i32, String> {
+            let value = attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+            match value {
+                Some(value) => value.parse().map_err(|_| format!("line {}: bad value for {}: {}", line_number, key, value)),
+                None => Err(format!("line {}: {} is missing {}", line_number, tag, key)),
+            }
+        };
+
+        match tag.as_str() {
+            "common" => {
+                line_height = Some(attribute("lineHeight")?);
+                if attribute("pages").unwrap_or(1) != 1 {
+                    return Err(format!("line {}: only fonts with a single page are supported", line_number));
+                }
+            },
+            "char" => {
+                if attribute("page").unwrap_or(0) != 0 {
+                    return Err(format!("line {}: char is not on page 0", line_number));
+                }
+                let id = attribute("id")?;
+                if id < 0 {
+                    return Err(format!("line {}: bad char id {}", line_number, id));
+                }
+                let glyph = Glyph {
+                    id: id as usize,
+                    x: attribute("x")?,
+                    y: attribute("y")?,
+                    width: attribute("width")?,
+                    height: attribute("height")?,
+                    x_offset: attribute("xoffset")?,
+                    y_offset: attribute("yoffset")?,
+                    x_advance: attribute("xadvance")?,
+                };
+                glyphs.insert(glyph.id, glyph);
+            },
+            "kerning" => {
+                let first = attribute("first")?;
+                let second = attribute("second")?;
+                kerning.insert((first as usize, second as usize), attribute("amount")?);
+            },
+            _ => {},
+        }
+    }
+
+    let line_height = line_height.ok_or_else(|| String::from("no common line"))?;
+
+    if glyphs.is_empty() {
+        return Err(String::from("no chars"));
+    }
+
+    Ok(Font { line_height, glyphs, kerning })
+}
+
+// Split a descriptor line into its tag and key=value attributes. Values may be
+// quoted, in which case they can contain spaces.
+
+fn parse_line(line: &str) -> Option<(String, Vec<(String, String)>)> {
+    let mut chars = line.trim().chars().peekable();
+
+    let tag: String = chars.by_ref().take_while(|c| !c.is_whitespace()).collect();
+    if tag.is_empty() {
+        return None;
+    }
+
+    let mut attributes = Vec::new();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let key: String = chars.by_ref().take_while(|&c| c != '=').collect();
+        if key.is_empty() {
+            break;
+        }
+
+        let value: String =
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                chars.by_ref().take_while(|&c| c != '"').collect()
+            } else {
+                chars.by_ref().take_while(|c| !c.is_whitespace()).collect()
+            };
+
+        attributes.push((String::from(key.trim()), value));
+    }
+
+    Some((tag, attributes))
+}
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct Glyph {
     pub id: usize,
     pub x: i32,
@@ -1,19 +1,46 @@
 use rand::SeedableRng;
 use std::cmp::{min, max};
+use std::collections::BinaryHeap;
 
-use crate::cell_grid::{TURNS_UNDERWATER_MAX, CellGrid, CellType, ItemKind, Map, Player, Random, make_player, tile_def};
+use crate::animation::{Animation, fade_color, new_animation, scale_brightness};
+use crate::audio::{Audio, Sound, new_audio};
+use crate::cell_grid::{ADJACENT_MOVES, TURNS_UNDERWATER_MAX, CellGrid, CellType, INFINITE_COST, ItemKind, Map, Player, Random, State, make_player};
 use crate::color_preset;
 use crate::coord::Coord;
+use crate::dialogue::{Dialogue, new_dialogue};
 use crate::engine;
-use crate::fontdata;
-use crate::guard::{GuardMode, Lines, color_for_guard_kind, guard_act_all, new_lines, update_dir};
+use crate::key_bindings::{ACTIONS, PRESETS, Action, KeyBindings, Preset, action_from_index, dir_for_action, name_for_key, new_key_bindings, preset_from_index};
+use crate::guard::{GuardKind, GuardMode, color_for_guard_kind, guard_act_all, line_is_clear, update_dir};
+use crate::localization::{HELP_PAGE_COUNT, Language, Msg, format_text, language_from_index, text};
+use crate::map_layer::{MapLayer, new_map_layer};
+use crate::message_log::{LogEntry, MessageLog, new_message_log};
+use crate::narration::NarrationEvent;
 use crate::random_map;
-use crate::speech_bubbles::{get_horizontal_extents, puts_proportional, new_popups, Popups};
+use crate::render::{Quad, Renderer};
+use crate::speech_bubbles::{new_popups, PopupType, PopupView, Popups};
+use crate::text_layout::{Align, draw_text_layout, get_horizontal_extents, layout_text, line_height, line_width, puts_proportional};
+use crate::tiles::{Noise, item_def, tile_def};
+
+use multiarray::Array2D;
 
-const BAR_HEIGHT: i32 = fontdata::LINE_HEIGHT + 2;
 const BAR_BACKGROUND_COLOR: u32 = 0xff101010;
 
-const TILE_SIZE: i32 = 16;
+const TILE_SIZE: i32 = 16; // in the tile texture, and on screen at 1x scale
+
+// Color for parts of the map the player has seen but can't see right now
+const UNLIT_COLOR: u32 = color_preset::LIGHT_BLUE;
+
+// Largest world scale the player can zoom to
+const WORLD_SCALE_MAX: i32 = 4;
+
+// Auto-fit picks the largest scale that still shows at least this many cells.
+const AUTO_FIT_CELLS_X: i32 = 48;
+const AUTO_FIT_CELLS_Y: i32 = 32;
+
+const PLAYER_NOISE_RADIUS: i32 = 75;
+
+// Number of turns a noise's reach stays on screen, fading out
+const NOISE_DISPLAY_TURNS: usize = 3;
 
 const INITIAL_LEVEL: usize = 0;
 const SEE_ALL_DEFAULT: bool = false;
@@ -21,15 +48,72 @@ const SEE_ALL_DEFAULT: bool = false;
 pub struct Game {
     random: Random,
     level: usize,
+    turn: usize, // turns elapsed on the current level
     map: Map,
-    lines: Lines,
+    dialogue: Dialogue,
     popups: Popups,
+    animation: Animation,
+    map_layer: MapLayer,
+    water_on_screen: bool, // as of the last on_draw(); its ripples need redrawing
+    audio: Audio,
     player: Player,
     finished_level: bool,
     see_all: bool,
     show_msgs: bool,
     show_help: bool,
+    show_guard_vision: bool,
+    show_noise_preview: bool,
+    noises: Vec<NoiseDisplay>,
+    noise_preview: Option<NoiseDisplay>,
+    look_cursor: Option<Coord>, // examining the map when set
+    mouse_pos: Option<Coord>, // screen position of the mouse pointer
     help_page: usize,
+    key_bindings: KeyBindings,
+    options: Option<OptionsScreen>, // editing key bindings when set
+    world_scale: WorldScale,
+    screen_size: Coord, // as of the last on_resize()
+    show_minimap: bool,
+    show_overview: bool,
+    guard_sightings: Vec<Option<Coord>>, // where the player last saw each guard in map.guards
+    message_log: MessageLog,
+    show_message_log: bool,
+    message_log_scroll: usize, // lines hidden below the bottom of the panel
+    narration: Option<NarrationEvent>, // most recent, in case its popup needs redoing
+    milestones: Milestones, // as of the start of the turn
+    language: Language,
+}
+
+// Narrated state of play; narration happens when one of these changes during a turn
+
+#[derive(Clone, Copy)]
+struct Milestones {
+    alive: bool,
+    alarm: bool,
+    all_loot_collected: bool,
+    all_seen: bool,
+    finished_level: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum WorldScale {
+    AutoFit,
+    Fixed(i32), // multiple of TILE_SIZE
+}
+
+// State of the options screen. Row 0 picks a preset; the rest are ACTIONS in order.
+
+struct OptionsScreen {
+    row: usize,
+    preset: usize, // index into PRESETS
+    rebinding: bool, // waiting for a key to bind to the selected action
+}
+
+// Where a noise could be heard, and which guards heard it
+
+struct NoiseDisplay {
+    cells: Vec<Coord>,
+    heard_by: Vec<usize>, // indices into map.guards
+    age: usize, // turns since the noise was made
 }
 
 pub fn new_game(seed: u64) -> Game {
@@ -37,36 +121,78 @@ pub fn new_game(seed: u64) -> Game {
     let level = INITIAL_LEVEL;
     let mut map = random_map::generate_map(&mut random, level);
     let player = make_player(map.pos_start);
-    let lines = new_lines();
+    let language = Language::English;
+    let dialogue = new_dialogue(language);
     let popups = new_popups();
 
     update_map_visibility(&mut map, player.pos);
 
-    Game {
+    let mut game = Game {
         random,
         level,
-        lines,
+        turn: 0,
+        dialogue,
         popups,
+        animation: new_animation(player.pos),
+        map_layer: new_map_layer(),
+        water_on_screen: false,
+        audio: new_audio(),
         map,
         player,
         finished_level: false,
         see_all: SEE_ALL_DEFAULT,
         show_msgs: true,
         show_help: false,
+        show_guard_vision: false,
+        show_noise_preview: false,
+        noises: Vec::new(),
+        noise_preview: None,
+        look_cursor: None,
+        mouse_pos: None,
         help_page: 0,
-    }
+        key_bindings: new_key_bindings(Preset::Standard),
+        options: None,
+        world_scale: WorldScale::AutoFit,
+        screen_size: Coord(0, 0),
+        show_minimap: true,
+        show_overview: false,
+        guard_sightings: Vec::new(),
+        message_log: new_message_log(),
+        show_message_log: false,
+        message_log_scroll: 0,
+        narration: None,
+        milestones: Milestones { alive: true, alarm: false, all_loot_collected: false, all_seen: false, finished_level: false },
+        language,
+    };
+
+    update_guard_sightings(&mut game);
+    narrate(&mut game, NarrationEvent::LevelStart(level));
+
+    game
 }
 
 fn restart_game(game: &mut Game) {
     game.level = INITIAL_LEVEL;
+    game.turn = 0;
     game.map = random_map::generate_map(&mut game.random, game.level);
+    game.dialogue.new_level();
+    game.animation.finish();
     game.finished_level = false;
     game.player = make_player(game.map.pos_start);
     game.show_msgs = true;
     game.show_help = false;
     game.popups = new_popups();
+    game.noises.clear();
+    game.look_cursor = None;
+    game.show_overview = false;
+    game.guard_sightings.clear();
+    game.message_log.clear();
+    game.show_message_log = false;
 
     update_map_visibility(&mut game.map, game.player.pos);
+    update_noise_preview(game);
+    update_guard_sightings(game);
+    narrate(game, NarrationEvent::LevelStart(game.level));
 
     engine::invalidate_screen();
 }
@@ -75,51 +201,36 @@ fn finished_level(map: &Map) -> bool {
     map.all_loot_collected() && map.all_seen()
 }
 
-pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
+pub fn on_draw(game: &mut Game, renderer: &mut dyn Renderer, screen_size_x: i32, screen_size_y: i32) {
+    let scale = world_scale(game, screen_size_x, screen_size_y);
+    let tile_size = TILE_SIZE * scale;
+    let view_offset = animated_viewport_offset(game, screen_size_x, screen_size_y);
+
+    // Base map
+
+    update_map_layer(game, view_offset, tile_size, Coord(screen_size_x, screen_size_y));
+    game.map_layer.draw(renderer, view_offset, scale);
+
+    let game: &Game = game;
     let map = &game.map;
     let items = &game.map.items;
     let player = &game.player;
     let guards = &game.map.guards;
 
-    let map_size_x = map.cells.extents()[0];
-    let map_size_y = map.cells.extents()[1];
-
-    let view_min = Coord(0, BAR_HEIGHT);
-    let view_max = Coord(screen_size_x, screen_size_y - BAR_HEIGHT);
-    let view_offset = viewport_offset(
-        view_min,
-        view_max,
-        Coord(map_size_x as i32, map_size_y as i32),
-        player.pos);
+    let player_tween = game.animation.player_offset(player.pos, tile_size);
 
-    let put_tile = |tile_index: u32, world_x: i32, world_y: i32, color: u32| {
-        let dest_x = world_x * TILE_SIZE + view_offset.0;
-        let dest_y = world_y * TILE_SIZE + view_offset.1;
-        draw_tile_by_index(tile_index, dest_x, dest_y, color);
+    let put_tile = |renderer: &mut dyn Renderer, tile_index: u32, world_x: i32, world_y: i32, color: u32| {
+        let dest_x = world_x * tile_size + view_offset.0;
+        let dest_y = world_y * tile_size + view_offset.1;
+        draw_scaled_tile_by_index(renderer, tile_index, dest_x, dest_y, tile_size, color);
     };
 
-    let put_offset_tile = |tile_index: u32, world_x: i32, world_y: i32, color: u32, add_x: i32, add_y: i32| {
-        let dest_x = world_x * TILE_SIZE + view_offset.0 + add_x;
-        let dest_y = world_y * TILE_SIZE + view_offset.1 + add_y;
-        draw_tile_by_index(tile_index, dest_x, dest_y, color);
+    // Actors are drawn partway between cells while they move. Offsets are in unscaled pixels
+    let put_actor_tile = |renderer: &mut dyn Renderer, tile_index: u32, pos: Coord, tween: Coord, color: u32, add_x: i32, add_y: i32| {
+        let dest = view_offset + pos * tile_size + tween + Coord(add_x, add_y) * scale;
+        draw_scaled_tile_by_index(renderer, tile_index, dest.0, dest.1, tile_size, color);
     };
 
-    // Base map
-
-    const UNLIT_COLOR: u32 = color_preset::LIGHT_BLUE;
-
-    for x in 0..map_size_x {
-        for y in 0..map_size_y {
-            let cell = &map.cells[[x, y]];
-            if !cell.seen && !game.see_all {
-                continue;
-            }
-            let tile = tile_def(cell.cell_type);
-            let color = if cell.lit || tile.ignores_lighting {tile.color} else {UNLIT_COLOR};
-            put_tile(tile.glyph, x as i32, y as i32, color);
-        }
-    }
-
     // Draw the inner region
 
     /*
@@ -130,7 +241,7 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
                 let cell = &map.cells[[x, y]];
                 if cell.inner {
                     let pos = view_offset + Coord(x as i32, y as i32) * TILE_SIZE;
-                    engine::draw_rect(pos.0, pos.1, TILE_SIZE, TILE_SIZE, color);
+                    renderer.draw_rect(pos.0, pos.1, TILE_SIZE, TILE_SIZE, color);
                 }
             }
         }
@@ -146,7 +257,7 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
             let digit = (d % 10) + 48;
             for x in region.rect.pos_min.0 .. region.rect.pos_max.0 {
                 for y in region.rect.pos_min.1 .. region.rect.pos_max.1 {
-                    put_tile(digit as u32, x as i32, y as i32, 0x60ffff00);
+                    put_tile(renderer, digit as u32, x as i32, y as i32, 0x60ffff00);
                 }
             }
         }
@@ -162,7 +273,7 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
             let digit = (d % 10) + 48;
             for x in region.rect.pos_min.0 .. region.rect.pos_max.0 {
                 for y in region.rect.pos_min.1 .. region.rect.pos_max.1 {
-                    put_tile(digit as u32, x as i32, y as i32, 0x60ff00ff);
+                    put_tile(renderer, digit as u32, x as i32, y as i32, 0x60ff00ff);
                 }
             }
         }
@@ -176,9 +287,27 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
         if !cell.seen && !game.see_all {
             continue;
         }
-        let glyph = glyph_for_item(item.kind);
+        let glyph = item_def(item.kind).glyph;
         let color = if cell.lit {color_for_item(item.kind)} else {UNLIT_COLOR};
-        put_tile(glyph, item.pos.0, item.pos.1, color);
+        put_tile(renderer, glyph, item.pos.0, item.pos.1, color);
+    }
+
+    // What the guards can see
+
+    if game.show_guard_vision {
+        draw_guard_vision(renderer, game, view_offset, tile_size, screen_size_x, screen_size_y);
+    }
+
+    // Noises' reach
+
+    for noise in &game.noises {
+        let fade = (NOISE_DISPLAY_TURNS - noise.age) as u32;
+        let alpha = 0x60 * fade / NOISE_DISPLAY_TURNS as u32;
+        draw_noise_cells(renderer, game, view_offset, tile_size, noise, (alpha << 24) | 0x00fefe54);
+    }
+
+    if let Some(noise) = &game.noise_preview {
+        draw_noise_cells(renderer, game, view_offset, tile_size, noise, 0x30fefe54);
     }
 
     // Halo around player
@@ -187,10 +316,10 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
     {
         let pos = player.pos * TILE_SIZE + view_offset + Coord(-8, -8);
         let color = 0x40ffffff;
-        draw_tile_by_index(228, pos.0, pos.1, color);
-        draw_tile_by_index(229, pos.0 + TILE_SIZE, pos.1, color);
-        draw_tile_by_index(230, pos.0, pos.1 + TILE_SIZE, color);
-        draw_tile_by_index(231, pos.0 + TILE_SIZE, pos.1 + TILE_SIZE, color);
+        draw_tile_by_index(renderer, 228, pos.0, pos.1, color);
+        draw_tile_by_index(renderer, 229, pos.0 + TILE_SIZE, pos.1, color);
+        draw_tile_by_index(renderer, 230, pos.0, pos.1 + TILE_SIZE, color);
+        draw_tile_by_index(renderer, 231, pos.0 + TILE_SIZE, pos.1 + TILE_SIZE, color);
     }
     */
 
@@ -203,10 +332,10 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
         let view_edge_max = Coord(min((map_size_x as i32) * TILE_SIZE + view_offset.0, view_max.0), min((map_size_y as i32) * TILE_SIZE + view_offset.1, view_max.1));
         let color = 0x40ffffff;
 
-        draw_tile_by_index(232, view_edge_min.0, pos.1, color);
-        draw_tile_by_index(233, view_edge_max.0 - TILE_SIZE, pos.1, color);
-        draw_tile_by_index(234, pos.0, view_edge_min.1, color);
-        draw_tile_by_index(235, pos.0, view_edge_max.1 - TILE_SIZE, color);
+        draw_tile_by_index(renderer, 232, view_edge_min.0, pos.1, color);
+        draw_tile_by_index(renderer, 233, view_edge_max.0 - TILE_SIZE, pos.1, color);
+        draw_tile_by_index(renderer, 234, pos.0, view_edge_min.1, color);
+        draw_tile_by_index(renderer, 235, pos.0, view_edge_max.1 - TILE_SIZE, color);
     }
     */
 
@@ -219,19 +348,19 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
         let hidden = player.hidden(map);
 
         let color =
-            if player.damaged_last_turn {0xff0000ff}
+            if player.damaged_last_turn && game.animation.damage_flash() > 0.0 {0xff0000ff}
             else if player.noisy {color_preset::LIGHT_CYAN}
             else if hidden {0xd0101010}
             else if !lit {color_preset::LIGHT_BLUE}
             else if let Some(guard_kind) = player.disguise {color_for_guard_kind(guard_kind)}
             else {color_preset::LIGHT_GRAY};
 
-        put_tile(tile_index, player.pos.0, player.pos.1, color);
+        put_actor_tile(renderer, tile_index, player.pos, player_tween, color, 0, 0);
     }
 
     // Guards
 
-    for guard in guards {
+    for (i_guard, guard) in guards.iter().enumerate() {
         let tile_index = 212 + tile_index_offset_for_dir(guard.dir);
         let cell = &map.cells[[guard.pos.0 as usize, guard.pos.1 as usize]];
         
@@ -253,20 +382,35 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
                 color_for_guard_kind(guard.kind)
             };
 
-        put_tile(tile_index, guard.pos.0, guard.pos.1, color);
+        put_actor_tile(renderer, tile_index, guard.pos, game.animation.guard_offset(i_guard, guard.pos, tile_size), color, 0, 0);
     }
 
     // Guard overhead icons
 
-    for guard in guards {
+    for (i_guard, guard) in guards.iter().enumerate() {
         if let Some((tile_index, color)) = guard.overhead_icon_and_color(map, player, game.see_all) {
-            put_offset_tile(tile_index, guard.pos.0, guard.pos.1, color, 0, 10);
+            put_actor_tile(renderer, tile_index, guard.pos, game.animation.guard_offset(i_guard, guard.pos, tile_size), color, 0, 10);
+        }
+    }
+
+    // Markers on guards that heard a noise
+
+    for noise in game.noises.iter().chain(game.noise_preview.iter()) {
+        for &i_guard in &noise.heard_by {
+            if let Some(guard) = guards.get(i_guard) {
+                let cell = &map.cells[[guard.pos.0 as usize, guard.pos.1 as usize]];
+                if game.see_all || cell.seen || guard.speaking {
+                    let tween = game.animation.guard_offset(i_guard, guard.pos, tile_size);
+                    put_actor_tile(renderer, 174, guard.pos, tween, color_preset::LIGHT_CYAN, -10, 0);
+                    put_actor_tile(renderer, 175, guard.pos, tween, color_preset::LIGHT_CYAN, 10, 0);
+                }
+            }
         }
     }
 
     // Player overhead icon
 
-    put_offset_tile(218, player.pos.0, player.pos.1, color_preset::LIGHT_YELLOW, 0, 10);
+    put_actor_tile(renderer, 218, player.pos, player_tween, color_preset::LIGHT_YELLOW, 0, 10);
 
     // Draw a guard's distance field to goal region
 
@@ -283,7 +427,7 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
                     let digit = (d % 10) + 48;
                     let band = d / 10;
                     let color = if band == 0 {color_preset::WHITE} else if band == 1 {color_preset::LIGHT_YELLOW} else {color_preset::DARK_GRAY};
-                    put_tile(digit as u32, x as i32, y as i32, color);
+                    put_tile(renderer, digit as u32, x as i32, y as i32, color);
                 }
             }
         }
@@ -299,14 +443,14 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
             let region = &map.patrol_regions[guard.region_prev];
             let pos = view_offset + region.pos_min * TILE_SIZE;
             let size = (region.pos_max - region.pos_min) * TILE_SIZE;
-            engine::draw_rect(pos.0, pos.1, size.0, size.1, COLOR);
+            renderer.draw_rect(pos.0, pos.1, size.0, size.1, COLOR);
         }
         if guard.region_goal != crate::cell_grid::INVALID_REGION {
             const COLOR: u32 = 0x4000ff00;
             let region = &map.patrol_regions[guard.region_goal];
             let pos = view_offset + region.pos_min * TILE_SIZE;
             let size = (region.pos_max - region.pos_min) * TILE_SIZE;
-            engine::draw_rect(pos.0, pos.1, size.0, size.1, COLOR);
+            renderer.draw_rect(pos.0, pos.1, size.0, size.1, COLOR);
         }
     }
     */
@@ -314,23 +458,106 @@ pub fn on_draw(game: &Game, screen_size_x: i32, screen_size_y: i32) {
     // Speech bubbles and sounds
 
     if game.show_msgs {
-        game.popups.draw(
-            screen_size_x,
-            screen_size_y,
-            Coord(TILE_SIZE, TILE_SIZE),
-            view_offset,
-            game.player.pos
-        );
+        let popup_view = PopupView {
+            screen_size: Coord(screen_size_x, screen_size_y),
+            scale: Coord(tile_size, tile_size),
+            offset: view_offset,
+            focus: game.player.pos,
+        };
+        if game.animation.popup_fade_out() > 0.0 {
+            game.animation.popups_prev().draw(renderer, &popup_view, game.animation.popup_fade_out());
+        }
+        game.popups.draw(renderer, &popup_view, game.animation.popup_fade_in());
+    }
+
+    // Flash the screen when the player is hurt
+
+    if player.damaged_last_turn {
+        let flash = game.animation.damage_flash();
+        if flash > 0.0 {
+            renderer.draw_rect(0, 0, screen_size_x, screen_size_y, fade_color(0x600000ff, flash));
+        }
+    }
+
+    // Look cursor and description
+
+    if let Some(pos_cursor) = game.look_cursor {
+        let pos = view_offset + pos_cursor * tile_size;
+        draw_frame(renderer, pos.0, pos.1, tile_size, tile_size, 2, color_preset::LIGHT_YELLOW);
+        draw_look_panel(renderer, game, pos_cursor, screen_size_y);
+    } else if let Some(pos_mouse) = mouse_cell(game, screen_size_x, screen_size_y) {
+        if !game.show_help && on_level(&map.cells, pos_mouse) {
+            let pos = view_offset + pos_mouse * tile_size;
+            draw_frame(renderer, pos.0, pos.1, tile_size, tile_size, 1, color_preset::LIGHT_GRAY);
+            draw_look_panel(renderer, game, pos_mouse, screen_size_y);
+        }
+    }
+
+    // Map overviews
+
+    if game.show_overview {
+        draw_overview(renderer, game, screen_size_x, screen_size_y);
+    } else if game.show_minimap {
+        draw_minimap(renderer, game, view_offset, tile_size, screen_size_x, screen_size_y);
+    }
+
+    if game.show_message_log {
+        draw_message_log(renderer, game, screen_size_x, screen_size_y);
     }
 
     // Help and status
 
     if game.show_help {
-        draw_help(screen_size_x, screen_size_y, game.help_page);
+        draw_help(renderer, screen_size_x, screen_size_y, game.language, &game.key_bindings, game.help_page);
+    } else if let Some(options) = &game.options {
+        draw_options(renderer, screen_size_x, screen_size_y, game.language, &game.key_bindings, options);
+    }
+
+    draw_top_status_bar(renderer, screen_size_x, screen_size_y, game);
+    draw_bottom_status_bar(renderer, screen_size_x, screen_size_y, game);
+}
+
+fn draw_guard_vision(renderer: &mut dyn Renderer, game: &Game, view_offset: Coord, tile_size: i32, screen_size_x: i32, screen_size_y: i32) {
+    const LIT_COLOR: u32 = 0x5054fefe;
+    const UNLIT_COLOR: u32 = 0x50fe5454;
+
+    let map = &game.map;
+
+    // World-space bounds of the screen
+
+    let world_min = Coord(-view_offset.0 / tile_size - 1, -view_offset.1 / tile_size - 1);
+    let world_max = Coord((screen_size_x - view_offset.0) / tile_size + 1, (screen_size_y - view_offset.1) / tile_size + 1);
+
+    for guard in &map.guards {
+        let cell = &map.cells[[guard.pos.0 as usize, guard.pos.1 as usize]];
+        if !(game.see_all || cell.seen || guard.speaking) {
+            continue;
+        }
+
+        let radius = guard.sight_radius();
+        if guard.pos.0 + radius < world_min.0 || guard.pos.0 - radius > world_max.0 ||
+           guard.pos.1 + radius < world_min.1 || guard.pos.1 - radius > world_max.1 {
+            continue;
+        }
+
+        guard.for_each_visible_cell(map, |pos, lit| {
+            if !game.see_all && !map.cells[[pos.0 as usize, pos.1 as usize]].seen {
+                return;
+            }
+            let dest = view_offset + pos * tile_size;
+            renderer.draw_rect(dest.0, dest.1, tile_size, tile_size, if lit {LIT_COLOR} else {UNLIT_COLOR});
+        });
     }
+}
 
-    draw_top_status_bar(screen_size_x, screen_size_y, game);
-    draw_bottom_status_bar(screen_size_x, screen_size_y, game);
+fn draw_noise_cells(renderer: &mut dyn Renderer, game: &Game, view_offset: Coord, tile_size: i32, noise: &NoiseDisplay, color: u32) {
+    for pos in &noise.cells {
+        if !game.see_all && !game.map.cells[[pos.0 as usize, pos.1 as usize]].seen {
+            continue;
+        }
+        let dest = view_offset + *pos * tile_size;
+        renderer.draw_rect(dest.0, dest.1, tile_size, tile_size, color);
+    }
 }
 
 fn tile_index_offset_for_dir(dir: Coord) -> u32 {
@@ -341,10 +568,129 @@ fn tile_index_offset_for_dir(dir: Coord) -> u32 {
     else {3}
 }
 
-fn viewport_offset(viewport_screen_min: Coord, viewport_screen_max: Coord, world_size: Coord, world_focus: Coord) -> Coord {
+// Multiple of TILE_SIZE at which to draw the world, given the current screen size
+
+fn world_scale(game: &Game, screen_size_x: i32, screen_size_y: i32) -> i32 {
+    match game.world_scale {
+        WorldScale::Fixed(scale) => scale,
+        WorldScale::AutoFit => {
+            let scale_x = screen_size_x / (AUTO_FIT_CELLS_X * TILE_SIZE);
+            let scale_y = (screen_size_y - 2 * bar_height()) / (AUTO_FIT_CELLS_Y * TILE_SIZE);
+            min(scale_x, scale_y).clamp(1, WORLD_SCALE_MAX)
+        },
+    }
+}
+
+fn zoom(game: &mut Game, scale_delta: i32) {
+    let scale = world_scale(game, game.screen_size.0, game.screen_size.1);
+    game.world_scale = WorldScale::Fixed((scale + scale_delta).clamp(1, WORLD_SCALE_MAX));
+    engine::invalidate_screen();
+}
+
+// Animations run between turns; the screen is redrawn each frame while any are playing.
+
+pub fn on_tick(game: &mut Game, dt_ms: f32) {
+    let ripple_step = game.animation.ripple_step();
+    game.animation.advance(dt_ms);
+
+    let water_rippled = game.water_on_screen && game.animation.ripple_step() != ripple_step;
+    if game.animation.in_progress() || water_rippled {
+        engine::invalidate_screen();
+    }
+}
+
+pub fn on_resize(game: &mut Game, screen_size_x: i32, screen_size_y: i32) {
+    game.screen_size = Coord(screen_size_x, screen_size_y);
+}
+
+// Bring the cached map layer up to date with what the player has seen, and note
+// whether any rippling water is on screen.
+
+fn update_map_layer(game: &mut Game, view_offset: Coord, tile_size: i32, screen_size: Coord) {
+    let map_size_x = game.map.cells.extents()[0];
+    let map_size_y = game.map.cells.extents()[1];
+
+    game.map_layer.resize(Coord(map_size_x as i32, map_size_y as i32));
+    game.water_on_screen = false;
+
+    for x in 0..map_size_x {
+        for y in 0..map_size_y {
+            let pos = Coord(x as i32, y as i32);
+            let cell = &game.map.cells[[x, y]];
+            if !cell.seen && !game.see_all {
+                game.map_layer.set_cell(pos, None);
+                continue;
+            }
+            let tile = tile_def(cell.cell_type);
+            let mut color = if cell.lit || tile.ignores_lighting {tile.color} else {UNLIT_COLOR};
+            if cell.cell_type == CellType::GroundWater && cell.lit {
+                color = scale_brightness(color, game.animation.water_ripple(pos));
+                let screen_pos = view_offset + pos * tile_size;
+                game.water_on_screen |=
+                    screen_pos.0 < screen_size.0 && screen_pos.0 + tile_size > 0 &&
+                    screen_pos.1 < screen_size.1 && screen_pos.1 + tile_size > 0;
+            }
+            let dest = pos * TILE_SIZE;
+            game.map_layer.set_cell(pos, Some(tile_quad(tile.glyph, dest.0, dest.1, TILE_SIZE, color)));
+        }
+    }
+}
+
+// Screen position of the world origin, given the current screen size
+
+fn game_viewport_offset(game: &Game, screen_size_x: i32, screen_size_y: i32) -> Coord {
+    viewport_offset_for_focus(game, screen_size_x, screen_size_y, game.look_cursor.unwrap_or(game.player.pos))
+}
+
+// While the player moves, the view scrolls along with them
+
+fn animated_viewport_offset(game: &Game, screen_size_x: i32, screen_size_y: i32) -> Coord {
+    if game.look_cursor.is_some() {
+        return game_viewport_offset(game, screen_size_x, screen_size_y);
+    }
+
+    let offset_prev = viewport_offset_for_focus(game, screen_size_x, screen_size_y, game.animation.player_pos_prev());
+    let offset = viewport_offset_for_focus(game, screen_size_x, screen_size_y, game.player.pos);
+    game.animation.tween(offset_prev, offset)
+}
+
+fn viewport_offset_for_focus(game: &Game, screen_size_x: i32, screen_size_y: i32, focus: Coord) -> Coord {
+    let map_size_x = game.map.cells.extents()[0];
+    let map_size_y = game.map.cells.extents()[1];
+
+    let view_min = Coord(0, bar_height());
+    let view_max = Coord(screen_size_x, screen_size_y - bar_height());
+
+    viewport_offset(
+        view_min,
+        view_max,
+        TILE_SIZE * world_scale(game, screen_size_x, screen_size_y),
+        Coord(map_size_x as i32, map_size_y as i32),
+        focus)
+}
+
+// World cell under a screen position, or None if the position is over a status bar
+
+fn world_pos_from_screen(game: &Game, screen_pos: Coord, screen_size_x: i32, screen_size_y: i32) -> Option<Coord> {
+    if screen_pos.1 < bar_height() || screen_pos.1 >= screen_size_y - bar_height() {
+        return None;
+    }
+
+    let tile_size = TILE_SIZE * world_scale(game, screen_size_x, screen_size_y);
+    let view_offset = game_viewport_offset(game, screen_size_x, screen_size_y);
+    let pos = screen_pos - view_offset;
+
+    Some(Coord(pos.0.div_euclid(tile_size), pos.1.div_euclid(tile_size)))
+}
+
+fn mouse_cell(game: &Game, screen_size_x: i32, screen_size_y: i32) -> Option<Coord> {
+    game.mouse_pos.and_then(|pos| world_pos_from_screen(game, pos, screen_size_x, screen_size_y))
+}
+
+fn viewport_offset(viewport_screen_min: Coord, viewport_screen_max: Coord, tile_size: i32, world_size: Coord, world_focus: Coord) -> Coord {
     let viewport_screen_size = viewport_screen_max - viewport_screen_min;
-    let world_screen_size = Coord(TILE_SIZE, TILE_SIZE).mul_components(world_size);
-    let world_focus = Coord(TILE_SIZE, TILE_SIZE).mul_components(world_focus) + Coord(TILE_SIZE / 2, TILE_SIZE / 2);
+    let world_screen_size = Coord(tile_size, tile_size).mul_components(world_size);
+    let world_focus = Coord(tile_size, tile_size).mul_components(world_focus) + Coord(tile_size / 2, tile_size / 2);
 
     let world_offset_x =
         if world_screen_size.0 <= viewport_screen_size.0 {
@@ -363,38 +709,19 @@ fn viewport_offset(viewport_screen_min: Coord, viewport_screen_max: Coord, world
     viewport_screen_min + Coord(world_offset_x, world_offset_y)
 }
 
-fn glyph_for_item(kind: ItemKind) -> u32 {
-    match kind {
-        ItemKind::Chair => 148,
-        ItemKind::Table => 146,
-        ItemKind::Bush => 144,
-        ItemKind::Coin => 158,
-        ItemKind::DoorNS => 169,
-        ItemKind::DoorEW => 167,
-        ItemKind::PortcullisNS => 194,
-        ItemKind::PortcullisEW => 194,
-        ItemKind::Outfit(_) => 163,
-    }
-}
-
 fn color_for_item(kind: ItemKind) -> u32 {
     match kind {
-        ItemKind::Chair => color_preset::DARK_BROWN,
-        ItemKind::Table => color_preset::DARK_BROWN,
-        ItemKind::Bush => color_preset::DARK_GREEN,
-        ItemKind::Coin => color_preset::LIGHT_YELLOW,
-        ItemKind::DoorNS => color_preset::DARK_BROWN,
-        ItemKind::DoorEW => color_preset::DARK_BROWN,
-        ItemKind::PortcullisNS => color_preset::LIGHT_GRAY,
-        ItemKind::PortcullisEW => color_preset::LIGHT_GRAY,
-        ItemKind::Outfit(None) => color_preset::LIGHT_GRAY,
         ItemKind::Outfit(Some(guard_kind)) => color_for_guard_kind(guard_kind),
+        _ => item_def(kind).color,
     }
 }
 
 fn advance_to_level(game: &mut Game, level: usize) {
     game.level = level;
+    game.turn = 0;
     game.map = random_map::generate_map(&mut game.random, game.level);
+    game.dialogue.new_level();
+    game.animation.finish();
     game.finished_level = false;
 
     game.player.pos = game.map.pos_start;
@@ -409,8 +736,15 @@ fn advance_to_level(game: &mut Game, level: usize) {
     game.show_msgs = true;
     game.show_help = false;
     game.popups = new_popups();
+    game.noises.clear();
+    game.look_cursor = None;
+    game.show_overview = false;
+    game.guard_sightings.clear();
 
     update_map_visibility(&mut game.map, game.player.pos);
+    update_noise_preview(game);
+    update_guard_sightings(game);
+    narrate(game, NarrationEvent::LevelStart(game.level));
 
     engine::invalidate_screen();
 }
@@ -472,8 +806,15 @@ fn move_player(game: &mut Game, mut dpos: Coord) {
 
     let cell_type = game.map.cells[[game.player.pos.0 as usize, game.player.pos.1 as usize]].cell_type;
 
-    if dpos != Coord(0, 0) && cell_type == CellType::GroundWoodCreaky {
-        make_noise(&mut game.map, &mut game.player, &mut game.popups, "\u{ab}creak\u{bb}");
+    if dpos != Coord(0, 0) {
+        if let Some(noise) = tile_def(cell_type).noise {
+            make_noise(&mut game.map, &mut game.player, &mut game.popups, &mut game.noises, text(game.language, msg_for_noise(noise)));
+        }
+        game.audio.emit(tile_def(cell_type).footstep_sound, game.player.pos);
+    }
+
+    if game.player.gold > gold_prev {
+        game.audio.emit(Sound::Coin, game.player.pos);
     }
 
     // Mark player as suspicious
@@ -500,19 +841,175 @@ fn try_use_in_direction(game: &mut Game, dpos: Coord) {
         pre_turn(game);
         game.player.disguise = outfit_new;
         game.player.dir = update_dir(game.player.dir, dpos);
+        game.audio.emit(Sound::OutfitSwap, pos);
         game.player.suspicious = true;
         advance_time(game);
         engine::invalidate_screen();
     }
 }
 
-fn make_noise(map: &mut Map, player: &mut Player, popups: &mut Popups, noise: &'static str) {
+fn msg_for_noise(noise: Noise) -> Msg {
+    match noise {
+        Noise::Creak => Msg::Creak,
+    }
+}
+
+fn make_noise(map: &mut Map, player: &mut Player, popups: &mut Popups, noises: &mut Vec<NoiseDisplay>, noise: &'static str) {
     player.noisy = true;
     popups.noise(player.pos, noise);
 
-    for guard in map.guards_in_earshot(player.pos, 75) {
+    for guard in map.guards_in_earshot(player.pos, PLAYER_NOISE_RADIUS) {
         guard.hear_thief();
     }
+
+    noises.push(noise_display_from_earshot(map));
+}
+
+fn noise_display_from_earshot(map: &Map) -> NoiseDisplay {
+    NoiseDisplay {
+        cells: map.coords_in_earshot(),
+        heard_by: map.guards.iter().enumerate().filter(|(_, guard)| map.in_earshot(guard.pos)).map(|(i, _)| i).collect(),
+        age: 0,
+    }
+}
+
+// Multi-step movement: travel to a chosen cell, or explore toward the nearest
+// unseen area. Either one stops as soon as anything interesting happens.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Travel {
+    ToPos(Coord),
+    Explore,
+}
+
+const TRAVEL_STEPS_MAX: usize = 500;
+
+pub fn travel(game: &mut Game, travel: Travel) {
+    let level = game.level;
+
+    for _ in 0..TRAVEL_STEPS_MAX {
+        if game.player.health == 0 || game.level != level {
+            break;
+        }
+
+        let dpos = match travel_next_step(game, travel) {
+            Some(dpos) => dpos,
+            None => break,
+        };
+
+        let guards_in_view_prev = guards_in_view(game);
+        let health_prev = game.player.health;
+        let pos_expected = game.player.pos + dpos;
+
+        move_player(game, dpos);
+
+        if game.player.pos != pos_expected ||
+           game.player.noisy ||
+           game.player.health < health_prev ||
+           !game.popups.is_empty() {
+            break;
+        }
+
+        if guards_in_view(game).iter().any(|i_guard| !guards_in_view_prev.contains(i_guard)) {
+            break;
+        }
+    }
+
+    engine::invalidate_screen();
+}
+
+// Indices of the guards the player can currently see
+
+fn guards_in_view(game: &Game) -> Vec<usize> {
+    const VIEW_DIST_SQUARED_MAX: i32 = 400; // matches recompute_visibility()
+
+    let map = &game.map;
+    let pos_player = game.player.pos;
+
+    map.guards.iter().enumerate().filter(|(_, guard)| {
+        (guard.pos - pos_player).length_squared() <= VIEW_DIST_SQUARED_MAX &&
+        line_is_clear(pos_player, guard.pos, |x, y| map.blocks_player_sight(x, y))
+    }).map(|(i, _)| i).collect()
+}
+
+// Search outward from the player across seen cells for the travel destination and
+// return the first step of the cheapest path to it.
+
+fn travel_next_step(game: &Game, travel: Travel) -> Option<Coord> {
+    // Wading is slow and leaves the player short of air, so prefer dry land.
+    const WATER_COST: usize = 8;
+
+    let map = &game.map;
+    let pos_start = game.player.pos;
+    let known = |pos: Coord| game.see_all || map.cells[[pos.0 as usize, pos.1 as usize]].seen;
+
+    let is_goal = |pos: Coord| match travel {
+        Travel::ToPos(pos_goal) => pos == pos_goal,
+        Travel::Explore => {
+            ADJACENT_MOVES.iter().any(|(_, dir)| {
+                let pos_adj = pos + *dir;
+                on_level(&map.cells, pos_adj) && !known(pos_adj)
+            })
+        },
+    };
+
+    if let Travel::ToPos(pos_goal) = travel {
+        if !on_level(&map.cells, pos_goal) || !known(pos_goal) || pos_goal == pos_start {
+            return None;
+        }
+    }
+
+    let size_x = map.cells.extents()[0];
+    let size_y = map.cells.extents()[1];
+
+    let mut dist_field: Array2D<usize> = Array2D::new([size_x, size_y], INFINITE_COST);
+    let mut first_step: Array2D<Coord> = Array2D::new([size_x, size_y], Coord(0, 0));
+    let mut heap = BinaryHeap::new();
+
+    dist_field[[pos_start.0 as usize, pos_start.1 as usize]] = 0;
+    heap.push(State{dist: 0, pos: pos_start});
+
+    while let Some(State {dist, pos}) = heap.pop() {
+        let p = [pos.0 as usize, pos.1 as usize];
+        if dist > dist_field[p] {
+            continue;
+        }
+
+        if pos != pos_start && is_goal(pos) {
+            return Some(first_step[p]);
+        }
+
+        for (move_dir_cost, dir) in &ADJACENT_MOVES {
+            let pos_new = pos + *dir;
+            if !on_level(&map.cells, pos_new) || !known(pos_new) || blocked(map, pos, pos_new) {
+                continue;
+            }
+
+            let p_new = [pos_new.0 as usize, pos_new.1 as usize];
+            let cell_cost = if map.cells[p_new].cell_type == CellType::GroundWater {WATER_COST} else {0};
+            let dist_new = dist + move_dir_cost + cell_cost;
+
+            if dist_new < dist_field[p_new] {
+                dist_field[p_new] = dist_new;
+                first_step[p_new] = if pos == pos_start {*dir} else {first_step[p]};
+                heap.push(State{dist: dist_new, pos: pos_new});
+            }
+        }
+    }
+
+    None
+}
+
+// Show who would hear the player if they made a noise where they're standing.
+
+fn update_noise_preview(game: &mut Game) {
+    game.noise_preview =
+        if game.show_noise_preview {
+            game.map.compute_earshot(game.player.pos, PLAYER_NOISE_RADIUS);
+            Some(noise_display_from_earshot(&game.map))
+        } else {
+            None
+        };
 }
 
 fn halts_slide(map: &Map, pos: Coord) -> bool {
@@ -533,7 +1030,13 @@ fn halts_slide(map: &Map, pos: Coord) -> bool {
 
 fn pre_turn(game: &mut Game) {
     game.show_msgs = true;
+    game.animation.begin_turn(game.player.pos, &game.map.guards, &game.popups);
     game.popups.clear();
+    game.milestones = current_milestones(game);
+    for noise in game.noises.iter_mut() {
+        noise.age += 1;
+    }
+    game.noises.retain(|noise| noise.age < NOISE_DISPLAY_TURNS);
     game.player.noisy = false;
     game.player.suspicious = false;
     game.player.damaged_last_turn = false;
@@ -555,15 +1058,53 @@ fn advance_time(game: &mut Game) {
         game.player.turns_remaining_underwater = TURNS_UNDERWATER_MAX;
     }
 
-    guard_act_all(&mut game.random, game.see_all, &mut game.popups, &mut game.lines, &mut game.map, &mut game.player);
+    game.turn += 1;
+
+    guard_act_all(&mut game.random, game.see_all, game.turn, &mut game.popups, &mut game.dialogue, &mut game.map, &mut game.player);
+    emit_guard_sounds(game);
 
     update_map_visibility(&mut game.map, game.player.pos);
+    update_noise_preview(game);
+    update_guard_sightings(game);
 
     if finished_level(&game.map) {
         game.finished_level = true;
     }
 
     game.player.suspicious = false;
+
+    narrate_milestones(game);
+    log_popups(game);
+}
+
+// Guards' footsteps, and the sounds that go with what they said this turn
+
+fn emit_guard_sounds(game: &mut Game) {
+    for guard in game.map.guards.iter().filter(|guard| guard.has_moved) {
+        let cell_type = game.map.cells[[guard.pos.0 as usize, guard.pos.1 as usize]].cell_type;
+        game.audio.emit(tile_def(cell_type).footstep_sound, guard.pos);
+    }
+
+    for popup in game.popups.iter() {
+        match popup.popup_type {
+            PopupType::Damage => game.audio.emit(Sound::Damage, popup.world_origin),
+            PopupType::GuardSpeech => {
+                if let Some(guard) = game.map.guards.iter().find(|guard| guard.pos == popup.world_origin) {
+                    let sound = match (guard.mode, guard.kind) {
+                        (GuardMode::ChaseVisibleTarget, _) | (GuardMode::SearchForMissingLoot, _) => Sound::Shout,
+                        (_, GuardKind::Outer) => Sound::VoiceOuter,
+                        (_, GuardKind::Inner) => Sound::VoiceInner,
+                    };
+                    game.audio.emit(sound, guard.pos);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+pub fn play_queued_sounds(game: &mut Game) {
+    game.audio.play_queued(game.player.pos);
 }
 
 fn update_map_visibility(map: &mut Map, pos_viewer: Coord) {
@@ -629,6 +1170,14 @@ fn blocked(map: &Map, pos_old: Coord, pos_new: Coord) -> bool {
 pub fn on_key_down(game: &mut Game, key: i32, ctrl_key_down: bool, shift_key_down: bool) {
     let handle_key = if game.show_help {
         on_key_down_help_mode
+    } else if game.options.is_some() {
+        on_key_down_options_mode
+    } else if game.show_message_log {
+        on_key_down_message_log_mode
+    } else if game.show_overview {
+        on_key_down_overview_mode
+    } else if game.look_cursor.is_some() {
+        on_key_down_look_mode
     } else {
         on_key_down_game_mode
     };
@@ -637,13 +1186,11 @@ pub fn on_key_down(game: &mut Game, key: i32, ctrl_key_down: bool, shift_key_dow
 }
 
 fn on_key_down_game_mode(game: &mut Game, key: i32, ctrl_key_down: bool, shift_key_down: bool) {
-    if key == engine::KEY_SLASH {
-        game.show_help = true;
-        engine::invalidate_screen();
-    } else if key == engine::KEY_SPACE {
-        game.show_msgs = !game.show_msgs;
+    if key == engine::KEY_ESCAPE {
+        let preset = PRESETS.iter().position(|preset| *preset == game.key_bindings.preset).unwrap_or(0);
+        game.options = Some(OptionsScreen { row: 0, preset, rebinding: false });
         engine::invalidate_screen();
-    } else if let Some(dir) = dir_from_key(key, ctrl_key_down, shift_key_down) {
+    } else if let Some(dir) = dir_from_key(&game.key_bindings, key, ctrl_key_down, shift_key_down) {
         move_player(game, dir);
     } else if ctrl_key_down {
         match key {
@@ -683,40 +1230,80 @@ fn on_key_down_game_mode(game: &mut Game, key: i32, ctrl_key_down: bool, shift_k
             },
             _ => {}
         }
-    }
-}
-
-fn dir_from_key(key: i32, ctrl_key_down: bool, shift_key_down: bool) -> Option<Coord> {
-    if ctrl_key_down || shift_key_down {
-        let vertical_offset =
-            if ctrl_key_down {-1} else {0} +
-            if shift_key_down {1} else {0};
-        match key {
-            engine::KEY_LEFT => Some(Coord(-1, vertical_offset)),
-            engine::KEY_UP => Some(Coord(0, 1)),
-            engine::KEY_RIGHT => Some(Coord(1, vertical_offset)),
-            engine::KEY_DOWN => Some(Coord(0, -1)),
-            _ => None
-        }
-    } else {
-        match key {
-            engine::KEY_LEFT => Some(Coord(-1, 0)),
-            engine::KEY_UP | engine::KEY_NUMPAD8 | engine::KEY_K => Some(Coord(0, 1)),
-            engine::KEY_RIGHT => Some(Coord(1, 0)),
-            engine::KEY_DOWN | engine::KEY_NUMPAD2 | engine::KEY_J => Some(Coord(0, -1)),
-            engine::KEY_NUMPAD1 | engine::KEY_B => Some(Coord(-1, -1)),
-            engine::KEY_NUMPAD4 | engine::KEY_H => Some(Coord(-1, 0)),
-            engine::KEY_NUMPAD6 | engine::KEY_L => Some(Coord(1, 0)),
-            engine::KEY_NUMPAD3 | engine::KEY_N => Some(Coord(1, -1)),
-            engine::KEY_NUMPAD9 | engine::KEY_U => Some(Coord(1, 1)),
-            engine::KEY_NUMPAD7 | engine::KEY_Y => Some(Coord(-1, 1)),
-            engine::KEY_NUMPAD5 | engine::KEY_PERIOD => Some(Coord(0, 0)),
-            _ => None
-        }
-    }
-}
-
-fn on_key_down_help_mode(game: &mut Game, key: i32, ctrl_key_down: bool, _shift_key_down: bool) {
+    } else if let Some(action) = game.key_bindings.action_for_key(key) {
+        match action {
+            Action::Help => {
+                game.show_help = true;
+                engine::invalidate_screen();
+            },
+            Action::ToggleMessages => {
+                game.show_msgs = !game.show_msgs;
+                engine::invalidate_screen();
+            },
+            Action::ToggleGuardVision => {
+                game.show_guard_vision = !game.show_guard_vision;
+                engine::invalidate_screen();
+            },
+            Action::ToggleNoisePreview => {
+                game.show_noise_preview = !game.show_noise_preview;
+                update_noise_preview(game);
+                engine::invalidate_screen();
+            },
+            Action::Look => {
+                game.look_cursor = Some(game.player.pos);
+                engine::invalidate_screen();
+            },
+            Action::Explore => {
+                travel(game, Travel::Explore);
+            },
+            Action::ZoomIn => {
+                zoom(game, 1);
+            },
+            Action::ZoomOut => {
+                zoom(game, -1);
+            },
+            Action::ZoomToFit => {
+                game.world_scale = WorldScale::AutoFit;
+                engine::invalidate_screen();
+            },
+            Action::ToggleMinimap => {
+                game.show_minimap = !game.show_minimap;
+                engine::invalidate_screen();
+            },
+            Action::Overview => {
+                game.show_overview = true;
+                engine::invalidate_screen();
+            },
+            Action::MessageLog => {
+                game.show_message_log = true;
+                game.message_log_scroll = 0;
+                engine::invalidate_screen();
+            },
+            _ => {}
+        }
+    }
+}
+
+// Ctrl/Shift plus Left/Right always move diagonally; everything else goes through the key bindings.
+
+fn dir_from_key(key_bindings: &KeyBindings, key: i32, ctrl_key_down: bool, shift_key_down: bool) -> Option<Coord> {
+    if ctrl_key_down || shift_key_down {
+        let vertical_offset =
+            if ctrl_key_down {-1} else {0} +
+            if shift_key_down {1} else {0};
+        match key {
+            engine::KEY_LEFT => Some(Coord(-1, vertical_offset)),
+            engine::KEY_UP => Some(Coord(0, 1)),
+            engine::KEY_RIGHT => Some(Coord(1, vertical_offset)),
+            engine::KEY_DOWN => Some(Coord(0, -1)),
+            _ => None
+        }
+    } else {
+        key_bindings.action_for_key(key).and_then(dir_for_action)
+    }
+}
+
+fn on_key_down_help_mode(game: &mut Game, key: i32, ctrl_key_down: bool, _shift_key_down: bool) {
     if ctrl_key_down {
         return;
     }
@@ -726,14 +1313,14 @@ fn on_key_down_help_mode(game: &mut Game, key: i32, ctrl_key_down: bool, _shift_
             game.show_help = false;
             engine::invalidate_screen();
         },
-        engine::KEY_LEFT | engine::KEY_NUMPAD4 => {
+        _ if dir_from_key(&game.key_bindings, key, false, false) == Some(Coord(-1, 0)) => {
             if game.help_page > 0 {
                 game.help_page -= 1;
                 engine::invalidate_screen();
             }
         },
-        engine::KEY_RIGHT | engine::KEY_NUMPAD6 => {
-            if game.help_page < HELP_MESSAGES.len() - 1 {
+        _ if dir_from_key(&game.key_bindings, key, false, false) == Some(Coord(1, 0)) => {
+            if game.help_page < HELP_PAGE_COUNT - 1 {
                 game.help_page += 1;
                 engine::invalidate_screen();
             }
@@ -742,36 +1329,801 @@ fn on_key_down_help_mode(game: &mut Game, key: i32, ctrl_key_down: bool, _shift_
     }
 }
 
+fn on_key_down_look_mode(game: &mut Game, key: i32, ctrl_key_down: bool, shift_key_down: bool) {
+    if key == engine::KEY_ESCAPE || game.key_bindings.action_for_key(key) == Some(Action::Look) {
+        game.look_cursor = None;
+        engine::invalidate_screen();
+    } else if key == engine::KEY_ENTER || key == engine::KEY_NUMPAD_ENTER {
+        if let Some(pos_cursor) = game.look_cursor.take() {
+            travel(game, Travel::ToPos(pos_cursor));
+        }
+    } else if let Some(dir) = dir_from_key(&game.key_bindings, key, ctrl_key_down, shift_key_down) {
+        if let Some(pos_cursor) = game.look_cursor {
+            let pos_new = pos_cursor + dir;
+            if on_level(&game.map.cells, pos_new) {
+                game.look_cursor = Some(pos_new);
+                engine::invalidate_screen();
+            }
+        }
+    }
+}
+
+// Language
+
+pub fn set_language(game: &mut Game, language_index: i32) {
+    let language = match language_from_index(language_index) {
+        Some(language) => language,
+        None => return,
+    };
+
+    game.language = language;
+    game.dialogue.set_language(language);
+
+    // Redo narration that's still on screen, such as the level start message when the
+    // language is chosen right after startup. It has already been logged.
+
+    if let Some(event) = game.narration {
+        if game.popups.iter().any(|popup| popup.popup_type == PopupType::Narration) {
+            game.popups.clear_narration();
+            game.popups.narration(game.player.pos, text(language, Msg::Narration(event)));
+        }
+    }
+
+    engine::invalidate_screen();
+}
+
+// Options screen. It's navigated with fixed keys so a bad binding can't lock the player out.
+
+pub fn reset_key_bindings(game: &mut Game, preset_index: i32, saved_action_count: i32) {
+    game.key_bindings = new_key_bindings(preset_from_index(preset_index).unwrap_or(Preset::Standard));
+    game.key_bindings.unbind_saved_actions(max(0, saved_action_count) as usize);
+}
+
+pub fn bind_key(game: &mut Game, key: i32, action_index: i32) {
+    if let Some(action) = action_from_index(action_index) {
+        game.key_bindings.bind(key, action);
+    }
+}
+
+fn save_key_bindings(key_bindings: &KeyBindings) {
+    engine::save_key_bindings(key_bindings.preset as i32, ACTIONS.len() as i32, &key_bindings.to_key_action_pairs());
+}
+
+fn on_key_down_options_mode(game: &mut Game, key: i32, _ctrl_key_down: bool, _shift_key_down: bool) {
+    let options = match game.options.as_mut() {
+        Some(options) => options,
+        None => return,
+    };
+
+    let action = if options.row > 0 {Some(ACTIONS[options.row - 1])} else {None};
+
+    if options.rebinding {
+        if let Some(action) = action {
+            if key != engine::KEY_ESCAPE {
+                game.key_bindings.bind(key, action);
+                save_key_bindings(&game.key_bindings);
+            }
+        }
+        options.rebinding = false;
+    } else {
+        match key {
+            engine::KEY_ESCAPE => {
+                game.options = None;
+            },
+            engine::KEY_UP | engine::KEY_NUMPAD8 => {
+                options.row = if options.row > 0 {options.row - 1} else {ACTIONS.len()};
+            },
+            engine::KEY_DOWN | engine::KEY_NUMPAD2 => {
+                options.row = if options.row < ACTIONS.len() {options.row + 1} else {0};
+            },
+            engine::KEY_LEFT | engine::KEY_NUMPAD4 if action.is_none() => {
+                options.preset = (options.preset + PRESETS.len() - 1) % PRESETS.len();
+            },
+            engine::KEY_RIGHT | engine::KEY_NUMPAD6 if action.is_none() => {
+                options.preset = (options.preset + 1) % PRESETS.len();
+            },
+            engine::KEY_ENTER | engine::KEY_NUMPAD_ENTER => {
+                if action.is_some() {
+                    options.rebinding = true;
+                } else {
+                    game.key_bindings = new_key_bindings(PRESETS[options.preset]);
+                    save_key_bindings(&game.key_bindings);
+                }
+            },
+            engine::KEY_BACKSPACE | engine::KEY_DELETE => {
+                if let Some(action) = action {
+                    game.key_bindings.unbind_action(action);
+                    save_key_bindings(&game.key_bindings);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    engine::invalidate_screen();
+}
+
+// Names of the keys bound to an action, for the options screen and for filling in
+// help text
+
+fn key_names(language: Language, key_bindings: &KeyBindings, action: Action, separator: &str) -> String {
+    let names: Vec<String> = key_bindings.keys_for_action(action).into_iter().map(name_for_key).collect();
+    if names.is_empty() {String::from(text(language, Msg::NoKeys))} else {names.join(separator)}
+}
+
+fn draw_options(renderer: &mut dyn Renderer, screen_size_x: i32, screen_size_y: i32, language: Language, key_bindings: &KeyBindings, options: &OptionsScreen) {
+    const BOX_SIZE_X: i32 = 664;
+    const MARGIN: i32 = 24;
+    const KEYS_X_MIN: i32 = 240;
+
+    const SCREEN_DARKENING_COLOR: u32 = 0xa0101010;
+    const WINDOW_BACKGROUND_COLOR: u32 = 0xff404040;
+    const SELECTED_ROW_COLOR: u32 = 0xff606060;
+    const TEXT_COLOR: u32 = 0xffffffff;
+
+    // The title and a blank line, then the preset row and a row per action
+
+    let box_size_y = 2 * MARGIN + (ACTIONS.len() as i32 + 3) * line_height();
+    let box_min_x = (screen_size_x - BOX_SIZE_X) / 2;
+    let box_min_y = (screen_size_y - (bar_height() + box_size_y)) / 2 + bar_height();
+
+    renderer.draw_rect(0, bar_height(), screen_size_x, screen_size_y - 2 * bar_height(), SCREEN_DARKENING_COLOR);
+    renderer.draw_rect(box_min_x, box_min_y, BOX_SIZE_X, box_size_y, WINDOW_BACKGROUND_COLOR);
+
+    let x = box_min_x + MARGIN;
+    let mut y = box_min_y + box_size_y - (line_height() + MARGIN);
+
+    puts_proportional(renderer, x, y, text(language, Msg::KeyBindingsTitle), TEXT_COLOR);
+    y -= 2 * line_height();
+
+    // Keys go in a column to the right of the longest label.
+
+    let labels_width = ACTIONS.iter().map(|action| line_width(text(language, Msg::Action(*action)))).max().unwrap_or(0);
+    let keys_x = max(KEYS_X_MIN, labels_width + MARGIN);
+
+    for row in 0..=ACTIONS.len() {
+        if row == options.row {
+            renderer.draw_rect(x - MARGIN / 2, y, BOX_SIZE_X - MARGIN, line_height(), SELECTED_ROW_COLOR);
+        }
+
+        let (label, keys) = if row == 0 {
+            (text(language, Msg::PresetLabel), format!("\u{ab} {} \u{bb}", text(language, Msg::Preset(PRESETS[options.preset]))))
+        } else if row == options.row && options.rebinding {
+            (text(language, Msg::Action(ACTIONS[row - 1])), String::from(text(language, Msg::PressKeyToBind)))
+        } else {
+            let action = ACTIONS[row - 1];
+            (text(language, Msg::Action(action)), key_names(language, key_bindings, action, ", "))
+        };
+
+        puts_proportional(renderer, x, y, label, TEXT_COLOR);
+        puts_proportional(renderer, x + keys_x, y, &keys, TEXT_COLOR);
+
+        y -= line_height();
+    }
+}
+
+// Narration
+
+// Narration is logged right away, since the level start narration's popup is
+// gone before the end of the first turn.
+
+fn narrate(game: &mut Game, event: NarrationEvent) {
+    let msg = text(game.language, Msg::Narration(event));
+
+    game.narration = Some(event);
+    game.popups.narration(game.player.pos, msg);
+    game.message_log.add(LogEntry {
+        level: game.level,
+        turn: game.turn,
+        popup_type: PopupType::Narration,
+        speaker: "",
+        offset: Coord(0, 0),
+        msg,
+    });
+}
+
+fn current_milestones(game: &Game) -> Milestones {
+    Milestones {
+        alive: game.player.health > 0,
+        alarm: game.map.guards.iter().any(|guard| guard.mode == GuardMode::ChaseVisibleTarget),
+        all_loot_collected: game.map.all_loot_collected(),
+        all_seen: game.map.all_seen(),
+        finished_level: game.finished_level,
+    }
+}
+
+// Announce the most important thing that changed this turn. Only one, since
+// narration popups all go in the same place.
+
+fn narrate_milestones(game: &mut Game) {
+    let prev = game.milestones;
+    let next = current_milestones(game);
+
+    let event =
+        if prev.alive && !next.alive {
+            Some(NarrationEvent::PlayerDied)
+        } else if !prev.finished_level && next.finished_level {
+            Some(NarrationEvent::ExitReachable)
+        } else if !prev.alarm && next.alarm {
+            Some(if game.player.disguise.is_some() {NarrationEvent::DisguiseBlown} else {NarrationEvent::AlarmRaised})
+        } else if !prev.all_loot_collected && next.all_loot_collected {
+            Some(NarrationEvent::LastCoinTaken)
+        } else if !prev.all_seen && next.all_seen {
+            Some(NarrationEvent::AllSeen)
+        } else {
+            None
+        };
+
+    if let Some(event) = event {
+        narrate(game, event);
+    }
+}
+
+// Message log
+
+fn log_popups(game: &mut Game) {
+    for popup in game.popups.iter().filter(|popup| popup.popup_type != PopupType::Narration) {
+        let speaker = if popup.world_origin == game.player.pos {
+            text(game.language, Msg::You)
+        } else if let Some(guard) = game.map.guards.iter().find(|guard| guard.pos == popup.world_origin) {
+            text(game.language, Msg::GuardKind(guard.kind))
+        } else {
+            text(game.language, Msg::Someone)
+        };
+
+        game.message_log.add(LogEntry {
+            level: game.level,
+            turn: game.turn,
+            popup_type: popup.popup_type,
+            speaker,
+            offset: popup.world_origin - game.player.pos,
+            msg: popup.msg,
+        });
+    }
+}
+
+fn log_color(popup_type: PopupType) -> u32 {
+    match popup_type {
+        PopupType::Noise => color_preset::LIGHT_CYAN,
+        PopupType::Damage => color_preset::LIGHT_YELLOW,
+        PopupType::GuardSpeech => color_preset::WHITE,
+        PopupType::Narration => color_preset::LIGHT_GREEN,
+    }
+}
+
+// The log as display lines, with a heading at the start of each level
+
+fn message_log_lines(message_log: &MessageLog, language: Language) -> Vec<(String, u32)> {
+    let mut lines = Vec::new();
+    let mut level_prev = None;
+
+    for entry in message_log.iter() {
+        if level_prev != Some(entry.level) {
+            lines.push((format_text(language, Msg::LogLevelHeading, &[&(entry.level + 1)]), color_preset::LIGHT_GRAY));
+            level_prev = Some(entry.level);
+        }
+
+        if entry.popup_type == PopupType::Narration {
+            lines.push((format_text(language, Msg::LogNarration, &[&entry.turn, &entry.msg]), log_color(entry.popup_type)));
+            continue;
+        }
+
+        let dist = max(entry.offset.0.abs(), entry.offset.1.abs());
+        let place =
+            if dist == 0 {
+                String::from(text(language, Msg::Here))
+            } else {
+                format_text(language, Msg::DistanceAndDirection, &[&dist, &text(language, msg_for_dir(entry.offset))])
+            };
+        let msg = entry.msg.replace('\n', " ");
+
+        lines.push((format_text(language, Msg::LogSpeech, &[&entry.turn, &entry.speaker, &place, &msg]), log_color(entry.popup_type)));
+    }
+
+    lines
+}
+
+const MESSAGE_LOG_BOX_SIZE_Y_MAX: i32 = 600;
+const MESSAGE_LOG_MARGIN: i32 = 24;
+
+// The log box shrinks to fit between the status bars on short screens.
+
+fn message_log_box_size_y(screen_size_y: i32) -> i32 {
+    min(MESSAGE_LOG_BOX_SIZE_Y_MAX, screen_size_y - 2 * bar_height())
+}
+
+fn message_log_lines_per_page(screen_size_y: i32) -> usize {
+    max(1, (message_log_box_size_y(screen_size_y) - 2 * MESSAGE_LOG_MARGIN) / line_height()) as usize
+}
+
+fn on_key_down_message_log_mode(game: &mut Game, key: i32, _ctrl_key_down: bool, _shift_key_down: bool) {
+    let num_lines = message_log_lines(&game.message_log, game.language).len();
+    let page = message_log_lines_per_page(game.screen_size.1);
+    let scroll_max = num_lines.saturating_sub(page);
+
+    let scroll = match key {
+        engine::KEY_ESCAPE => {
+            game.show_message_log = false;
+            engine::invalidate_screen();
+            return;
+        },
+        _ if game.key_bindings.action_for_key(key) == Some(Action::MessageLog) => {
+            game.show_message_log = false;
+            engine::invalidate_screen();
+            return;
+        },
+        engine::KEY_UP | engine::KEY_NUMPAD8 => game.message_log_scroll + 1,
+        engine::KEY_DOWN | engine::KEY_NUMPAD2 => game.message_log_scroll.saturating_sub(1),
+        engine::KEY_PAGE_UP | engine::KEY_NUMPAD9 => game.message_log_scroll + page,
+        engine::KEY_PAGE_DOWN | engine::KEY_NUMPAD3 => game.message_log_scroll.saturating_sub(page),
+        engine::KEY_HOME | engine::KEY_NUMPAD7 => scroll_max,
+        engine::KEY_END | engine::KEY_NUMPAD1 => 0,
+        _ => return,
+    };
+
+    game.message_log_scroll = min(scroll, scroll_max);
+    engine::invalidate_screen();
+}
+
+fn draw_message_log(renderer: &mut dyn Renderer, game: &Game, screen_size_x: i32, screen_size_y: i32) {
+    const BOX_SIZE_X: i32 = 664;
+
+    const SCREEN_DARKENING_COLOR: u32 = 0xa0101010;
+    const WINDOW_BACKGROUND_COLOR: u32 = 0xff404040;
+    const TEXT_COLOR: u32 = 0xffffffff;
+
+    let box_size_y = message_log_box_size_y(screen_size_y);
+    let box_min_x = (screen_size_x - BOX_SIZE_X) / 2;
+    let box_min_y = min((screen_size_y - (bar_height() + box_size_y)) / 2 + bar_height(), screen_size_y - (bar_height() + box_size_y));
+
+    renderer.draw_rect(0, bar_height(), screen_size_x, screen_size_y - 2 * bar_height(), SCREEN_DARKENING_COLOR);
+    renderer.draw_rect(box_min_x, box_min_y, BOX_SIZE_X, box_size_y, WINDOW_BACKGROUND_COLOR);
+
+    let x = box_min_x + MESSAGE_LOG_MARGIN;
+    let mut y = box_min_y + box_size_y - (line_height() + MESSAGE_LOG_MARGIN);
+
+    let lines = message_log_lines(&game.message_log, game.language);
+
+    if lines.is_empty() {
+        puts_proportional(renderer, x, y, text(game.language, Msg::LogEmpty), TEXT_COLOR);
+        return;
+    }
+
+    let end = lines.len() - min(game.message_log_scroll, lines.len());
+    let start = end.saturating_sub(message_log_lines_per_page(screen_size_y));
+
+    for (line, color) in &lines[start..end] {
+        puts_proportional(renderer, x, y, line, *color);
+        y -= line_height();
+    }
+
+    // Scroll indicators
+
+    let more = text(game.language, Msg::More);
+    let (more_x_min, more_x_max) = get_horizontal_extents(more);
+    let more_x = box_min_x + BOX_SIZE_X - (MESSAGE_LOG_MARGIN + more_x_max - more_x_min);
+
+    if start > 0 {
+        puts_proportional(renderer, more_x, box_min_y + box_size_y - line_height(), more, TEXT_COLOR);
+    }
+    if end < lines.len() {
+        puts_proportional(renderer, more_x, box_min_y, more, TEXT_COLOR);
+    }
+}
+
+// Minimap and overview. Both show what the player knows: seen cells, loot
+// on them, and where each guard was last seen.
+
+const MINIMAP_MARGIN: i32 = 8;
+const MINIMAP_SIZE_MAX: i32 = 200; // pixels on the longer side
+
+fn update_guard_sightings(game: &mut Game) {
+    let num_guards = game.map.guards.len();
+    game.guard_sightings.resize(num_guards, None);
+
+    for i_guard in guards_in_view(game) {
+        game.guard_sightings[i_guard] = Some(game.map.guards[i_guard].pos);
+    }
+}
+
+fn minimap_color(cell_type: CellType) -> u32 {
+    let tile = tile_def(cell_type);
+    match cell_type {
+        CellType::GroundWater => color_preset::DARK_BLUE,
+        CellType::GroundGrass => 0xff004000,
+        _ if tile.blocks_player || tile.blocks_sight => color_preset::LIGHT_GRAY,
+        _ => color_preset::DARK_GRAY,
+    }
+}
+
+fn draw_map_summary(renderer: &mut dyn Renderer, game: &Game, origin: Coord, cell_size: i32) {
+    let map = &game.map;
+    let map_size_x = map.cells.extents()[0];
+    let map_size_y = map.cells.extents()[1];
+
+    let known = |pos: Coord| game.see_all || map.cells[[pos.0 as usize, pos.1 as usize]].seen;
+
+    let put_cell = |renderer: &mut dyn Renderer, pos: Coord, color: u32| {
+        let dest = origin + pos * cell_size;
+        renderer.draw_rect(dest.0, dest.1, cell_size, cell_size, color);
+    };
+
+    for x in 0..map_size_x {
+        for y in 0..map_size_y {
+            let cell = &map.cells[[x, y]];
+            if cell.seen || game.see_all {
+                put_cell(renderer, Coord(x as i32, y as i32), minimap_color(cell.cell_type));
+            }
+        }
+    }
+
+    for item in &map.items {
+        if item.kind == ItemKind::Coin && known(item.pos) {
+            put_cell(renderer, item.pos, color_preset::LIGHT_YELLOW);
+        }
+    }
+
+    let guards_visible = guards_in_view(game);
+
+    for (i_guard, guard) in map.guards.iter().enumerate() {
+        if game.see_all || guards_visible.contains(&i_guard) {
+            put_cell(renderer, guard.pos, color_preset::LIGHT_RED);
+        } else if let Some(pos) = game.guard_sightings.get(i_guard).copied().flatten() {
+            put_cell(renderer, pos, color_preset::DARK_RED);
+        }
+    }
+
+    put_cell(renderer, game.player.pos, color_preset::WHITE);
+}
+
+fn draw_minimap(renderer: &mut dyn Renderer, game: &Game, view_offset: Coord, tile_size: i32, screen_size_x: i32, screen_size_y: i32) {
+    const BACKGROUND_COLOR: u32 = 0xc0101010;
+    const VIEW_FRAME_COLOR: u32 = 0x80fefefe;
+
+    let map_size = Coord(game.map.cells.extents()[0] as i32, game.map.cells.extents()[1] as i32);
+    let cell_size = max(1, MINIMAP_SIZE_MAX / max(map_size.0, map_size.1));
+    let size = map_size * cell_size;
+    let origin = Coord(screen_size_x - MINIMAP_MARGIN - size.0, screen_size_y - bar_height() - MINIMAP_MARGIN - size.1);
+
+    renderer.draw_rect(origin.0 - 2, origin.1 - 2, size.0 + 4, size.1 + 4, BACKGROUND_COLOR);
+    draw_map_summary(renderer, game, origin, cell_size);
+
+    // Outline the part of the map that's on screen
+
+    let view_min = Coord(max(0, -view_offset.0 / tile_size), max(0, (bar_height() - view_offset.1) / tile_size));
+    let view_max = Coord(
+        min(map_size.0, (screen_size_x - view_offset.0 + tile_size - 1) / tile_size),
+        min(map_size.1, (screen_size_y - bar_height() - view_offset.1 + tile_size - 1) / tile_size));
+    if view_min.0 > 0 || view_min.1 > 0 || view_max.0 < map_size.0 || view_max.1 < map_size.1 {
+        let frame_pos = origin + view_min * cell_size;
+        let frame_size = (view_max - view_min) * cell_size;
+        draw_frame(renderer, frame_pos.0, frame_pos.1, frame_size.0, frame_size.1, 1, VIEW_FRAME_COLOR);
+    }
+}
+
+// Screen position and cell size for the full-screen overview
+
+fn overview_layout(game: &Game, screen_size_x: i32, screen_size_y: i32) -> (Coord, i32) {
+    let map_size = Coord(game.map.cells.extents()[0] as i32, game.map.cells.extents()[1] as i32);
+    let view_size = Coord(screen_size_x - 2 * MINIMAP_MARGIN, screen_size_y - 2 * (bar_height() + MINIMAP_MARGIN));
+    let cell_size = max(1, min(view_size.0 / map_size.0, view_size.1 / map_size.1));
+    let size = map_size * cell_size;
+    let origin = Coord((screen_size_x - size.0) / 2, (screen_size_y - size.1) / 2);
+    (origin, cell_size)
+}
+
+fn draw_overview(renderer: &mut dyn Renderer, game: &Game, screen_size_x: i32, screen_size_y: i32) {
+    const SCREEN_DARKENING_COLOR: u32 = 0xe0101010;
+
+    renderer.draw_rect(0, bar_height(), screen_size_x, screen_size_y - 2 * bar_height(), SCREEN_DARKENING_COLOR);
+
+    let (origin, cell_size) = overview_layout(game, screen_size_x, screen_size_y);
+    draw_map_summary(renderer, game, origin, cell_size);
+}
+
+fn on_key_down_overview_mode(game: &mut Game, key: i32, _ctrl_key_down: bool, _shift_key_down: bool) {
+    if key == engine::KEY_ESCAPE || game.key_bindings.action_for_key(key) == Some(Action::Overview) {
+        game.show_overview = false;
+        engine::invalidate_screen();
+    }
+}
+
+fn on_mouse_down_overview_mode(game: &mut Game, screen_pos: Coord, screen_size_x: i32, screen_size_y: i32) {
+    let (origin, cell_size) = overview_layout(game, screen_size_x, screen_size_y);
+    let pos = screen_pos - origin;
+    let pos = Coord(pos.0.div_euclid(cell_size), pos.1.div_euclid(cell_size));
+
+    game.show_overview = false;
+    engine::invalidate_screen();
+
+    if on_level(&game.map.cells, pos) && (game.see_all || game.map.cells[[pos.0 as usize, pos.1 as usize]].seen) {
+        game.look_cursor = Some(pos);
+    }
+}
+
+// Mouse and touch input. Screen positions are in drawing coordinates, with the
+// origin at the lower left.
+
+pub fn on_mouse_move(game: &mut Game, screen_x: i32, screen_y: i32, screen_size_x: i32, screen_size_y: i32) {
+    game.screen_size = Coord(screen_size_x, screen_size_y);
+    let mouse_cell_prev = mouse_cell(game, screen_size_x, screen_size_y);
+    game.mouse_pos = Some(Coord(screen_x, screen_y));
+    if mouse_cell(game, screen_size_x, screen_size_y) != mouse_cell_prev {
+        engine::invalidate_screen();
+    }
+}
+
+pub fn on_mouse_leave(game: &mut Game) {
+    if game.mouse_pos.is_some() {
+        game.mouse_pos = None;
+        engine::invalidate_screen();
+    }
+}
+
+pub fn on_mouse_down(game: &mut Game, screen_x: i32, screen_y: i32, screen_size_x: i32, screen_size_y: i32, button: i32, shift_key_down: bool) {
+    let screen_pos = Coord(screen_x, screen_y);
+    let world_pos = world_pos_from_screen(game, screen_pos, screen_size_x, screen_size_y);
+    let look = button == engine::MOUSE_BUTTON_RIGHT || shift_key_down;
+
+    if game.show_help {
+        on_mouse_down_help_mode(game, screen_pos, screen_size_x, world_pos.is_none());
+    } else if game.options.is_some() {
+        // The options screen is keyboard-only.
+    } else if game.show_message_log {
+        game.show_message_log = false;
+        engine::invalidate_screen();
+    } else if game.show_overview {
+        on_mouse_down_overview_mode(game, screen_pos, screen_size_x, screen_size_y);
+    } else if let Some(pos) = world_pos {
+        if game.look_cursor.is_some() {
+            on_mouse_down_look_mode(game, pos, look);
+        } else if look {
+            if on_level(&game.map.cells, pos) {
+                game.look_cursor = Some(pos);
+                engine::invalidate_screen();
+            }
+        } else if button == engine::MOUSE_BUTTON_LEFT {
+            on_click_map(game, pos);
+        }
+    } else if screen_y >= screen_size_y - bar_height() && game.look_cursor.is_none() {
+        // The top status bar doubles as a help button.
+        game.show_help = true;
+        engine::invalidate_screen();
+    }
+}
+
+pub fn on_touch_move(game: &mut Game, screen_x: i32, screen_y: i32, screen_size_x: i32, screen_size_y: i32) {
+    on_mouse_move(game, screen_x, screen_y, screen_size_x, screen_size_y);
+}
+
+// A touch acts on release, so players can drag a finger around to see what's
+// under it before committing to a move.
+
+pub fn on_touch_end(game: &mut Game, screen_x: i32, screen_y: i32, screen_size_x: i32, screen_size_y: i32) {
+    on_mouse_leave(game);
+    on_mouse_down(game, screen_x, screen_y, screen_size_x, screen_size_y, engine::MOUSE_BUTTON_LEFT, false);
+}
+
+// Clicking next to the player steps there (using an outfit, or leaving the level, if
+// that's what's there); clicking the player waits a turn. Clicking a known cell
+// further away travels to it, and anywhere else takes a step toward it.
+
+fn on_click_map(game: &mut Game, pos: Coord) {
+    if game.player.health == 0 {
+        restart_game(game);
+        return;
+    }
+
+    let dpos = pos - game.player.pos;
+    let adjacent = dpos.0.abs() <= 1 && dpos.1.abs() <= 1;
+    let known = on_level(&game.map.cells, pos) && (game.see_all || game.map.cells[[pos.0 as usize, pos.1 as usize]].seen);
+
+    if !adjacent && known {
+        let pos_prev = game.player.pos;
+        travel(game, Travel::ToPos(pos));
+        if game.player.pos != pos_prev {
+            return;
+        }
+    }
+
+    move_player(game, Coord(dpos.0.signum(), dpos.1.signum()));
+}
+
+fn on_mouse_down_look_mode(game: &mut Game, pos: Coord, look: bool) {
+    if look || !on_level(&game.map.cells, pos) {
+        game.look_cursor = None;
+        engine::invalidate_screen();
+    } else if game.look_cursor == Some(pos) {
+        game.look_cursor = None;
+        travel(game, Travel::ToPos(pos));
+    } else {
+        game.look_cursor = Some(pos);
+        engine::invalidate_screen();
+    }
+}
+
+// Clicking the left or right half of the screen pages through help; clicking
+// either status bar closes it.
+
+fn on_mouse_down_help_mode(game: &mut Game, screen_pos: Coord, screen_size_x: i32, on_status_bar: bool) {
+    if on_status_bar {
+        game.show_help = false;
+    } else if screen_pos.0 < screen_size_x / 2 {
+        if game.help_page > 0 {
+            game.help_page -= 1;
+        }
+    } else if game.help_page < HELP_PAGE_COUNT - 1 {
+        game.help_page += 1;
+    } else {
+        game.show_help = false;
+    }
+
+    engine::invalidate_screen();
+}
+
 // Tile-set drawing
 
-fn draw_tile_by_index(tile_index: u32, dest_x: i32, dest_y: i32, color: u32) {
-    const TEXTURE_INDEX: u32 = 0;
-    let src_x = ((tile_index & 15) * 16) as i32;
-    let src_y = (240 - (tile_index & !15)) as i32;
-    engine::draw_tile(dest_x, dest_y, TILE_SIZE, TILE_SIZE, color, TEXTURE_INDEX, src_x, src_y);
+fn draw_tile_by_index(renderer: &mut dyn Renderer, tile_index: u32, dest_x: i32, dest_y: i32, color: u32) {
+    draw_scaled_tile_by_index(renderer, tile_index, dest_x, dest_y, TILE_SIZE, color);
+}
+
+pub fn draw_scaled_tile_by_index(renderer: &mut dyn Renderer, tile_index: u32, dest_x: i32, dest_y: i32, dest_size: i32, color: u32) {
+    renderer.draw_quad(tile_quad(tile_index, dest_x, dest_y, dest_size, color));
+}
+
+fn tile_quad(tile_index: u32, dest_x: i32, dest_y: i32, dest_size: i32, color: u32) -> Quad {
+    Quad {
+        dest_x,
+        dest_y,
+        size_x: dest_size,
+        size_y: dest_size,
+        color,
+        texture_index: 0,
+        src_x: ((tile_index & 15) * 16) as i32,
+        src_y: (240 - (tile_index & !15)) as i32,
+        src_size_x: TILE_SIZE,
+        src_size_y: TILE_SIZE,
+    }
+}
+
+// The tile a quad from tile_quad draws, for renderers that don't use the tile texture
+
+pub fn quad_tile_index(quad: &Quad) -> Option<u32> {
+    if quad.texture_index != 0 || quad.src_size_x != TILE_SIZE || quad.src_size_y != TILE_SIZE || quad.src_x % TILE_SIZE != 0 || quad.src_y % TILE_SIZE != 0 {
+        return None;
+    }
+    Some(((240 - quad.src_y) + quad.src_x / TILE_SIZE) as u32)
+}
+
+// Rectangle outline
+
+fn draw_frame(renderer: &mut dyn Renderer, x: i32, y: i32, size_x: i32, size_y: i32, thickness: i32, color: u32) {
+    renderer.draw_rect(x, y, size_x, thickness, color);
+    renderer.draw_rect(x, y + size_y - thickness, size_x, thickness, color);
+    renderer.draw_rect(x, y + thickness, thickness, size_y - 2 * thickness, color);
+    renderer.draw_rect(x + size_x - thickness, y + thickness, thickness, size_y - 2 * thickness, color);
+}
+
+// Look mode
+
+fn describe_cell(game: &Game, pos: Coord) -> Vec<String> {
+    let map = &game.map;
+    let cell = &map.cells[[pos.0 as usize, pos.1 as usize]];
+
+    if !cell.seen && !game.see_all {
+        return vec![String::from(text(game.language, Msg::Unexplored))];
+    }
+
+    let mut lines = Vec::new();
+
+    let cell_type_name = text(game.language, Msg::CellType(cell.cell_type));
+    lines.push(format_text(game.language, if cell.lit {Msg::CellLit} else {Msg::CellDark}, &[&cell_type_name]));
+
+    for item in map.items.iter().filter(|item| item.pos == pos) {
+        lines.push(String::from(text(game.language, Msg::ItemKind(item.kind))));
+    }
+
+    if cell.hides_player || cell.cell_type == CellType::GroundWater {
+        lines.push(String::from(text(game.language, Msg::CanHideHere)));
+    }
+
+    if pos == game.player.pos {
+        lines.push(String::from(text(game.language, Msg::You)));
+    }
+
+    if let Some(guard) = map.guards.iter().find(|guard| guard.pos == pos) {
+        let guard_name = text(game.language, Msg::GuardKind(guard.kind));
+        let facing = text(game.language, msg_for_dir(guard.dir));
+        lines.push(format_text(game.language, Msg::GuardFacing, &[&guard_name, &facing]));
+        lines.push(String::from(text(game.language, Msg::GuardMode(guard.mode))));
+    }
+
+    // Guards who could see someone standing here
+
+    for guard in map.guards.iter().filter(|guard| guard.pos != pos) {
+        let guard_cell = &map.cells[[guard.pos.0 as usize, guard.pos.1 as usize]];
+        if !(game.see_all || guard_cell.seen || guard.speaking) {
+            continue;
+        }
+        if guard.can_see(map, pos, false) {
+            let guard_name = text(game.language, Msg::GuardKind(guard.kind)).to_lowercase();
+            let dir = text(game.language, msg_for_dir(guard.pos - pos));
+            lines.push(format_text(game.language, Msg::VisibleToGuard, &[&guard_name, &dir]));
+        }
+    }
+
+    lines
+}
+
+fn msg_for_dir(dir: Coord) -> Msg {
+    let x = dir.0.signum();
+    let y = dir.1.signum();
+    if dir.0.abs() > 2 * dir.1.abs() {
+        if x > 0 {Msg::East} else {Msg::West}
+    } else if dir.1.abs() > 2 * dir.0.abs() {
+        if y > 0 {Msg::North} else {Msg::South}
+    } else if y > 0 {
+        if x > 0 {Msg::Northeast} else {Msg::Northwest}
+    } else if x > 0 {
+        Msg::Southeast
+    } else {
+        Msg::Southwest
+    }
+}
+
+fn draw_look_panel(renderer: &mut dyn Renderer, game: &Game, pos: Coord, screen_size_y: i32) {
+    const MARGIN: i32 = 8;
+    const BACKGROUND_COLOR: u32 = 0xe0404040;
+    const TEXT_COLOR: u32 = 0xffffffff;
+
+    let lines = describe_cell(game, pos);
+
+    let width = lines.iter().map(|line| {
+        let (x_min, x_max) = get_horizontal_extents(line);
+        x_max - x_min
+    }).max().unwrap_or(0);
+
+    let size_x = width + 2 * MARGIN;
+    let size_y = lines.len() as i32 * line_height() + MARGIN;
+    let x = MARGIN;
+    let y_top = screen_size_y - bar_height() - MARGIN;
+
+    renderer.draw_rect(x, y_top - size_y, size_x, size_y, BACKGROUND_COLOR);
+
+    let mut y = y_top - line_height() - MARGIN / 2;
+    for line in &lines {
+        puts_proportional(renderer, x + MARGIN, y, line, TEXT_COLOR);
+        y -= line_height();
+    }
 }
 
 // Status bars
 
-fn draw_bottom_status_bar(screen_size_x: i32, _screen_size_y: i32, game: &Game) {
-    engine::draw_rect(0, 0, screen_size_x, BAR_HEIGHT, BAR_BACKGROUND_COLOR);
+// Bars are a line of text tall, plus a little padding.
+
+fn bar_height() -> i32 {
+    line_height() + 2
+}
+
+fn draw_bottom_status_bar(renderer: &mut dyn Renderer, screen_size_x: i32, _screen_size_y: i32, game: &Game) {
+    renderer.draw_rect(0, 0, screen_size_x, bar_height(), BAR_BACKGROUND_COLOR);
 
     let y_base = 2;
 
     const HEALTH_COLOR: u32 = 0xff0000a8;
     let mut x = 8;
-    x = puts_proportional(x, y_base, "Health", HEALTH_COLOR);
+    x = puts_proportional(renderer, x, y_base, text(game.language, Msg::Health), HEALTH_COLOR);
     x += 12;
 
     const TILE_HEALTHY: u32 = 5;
     for _ in 0..game.player.health {
-        draw_tile_by_index(TILE_HEALTHY, x, y_base + 5, HEALTH_COLOR);
+        draw_tile_by_index(renderer, TILE_HEALTHY, x, y_base + 5, HEALTH_COLOR);
         x += TILE_SIZE;
     }
 
     const TILE_UNHEALTHY: u32 = 7;
     for _ in game.player.health..game.player.max_health {
-        draw_tile_by_index(TILE_UNHEALTHY, x, y_base + 5, HEALTH_COLOR);
+        draw_tile_by_index(renderer, TILE_UNHEALTHY, x, y_base + 5, HEALTH_COLOR);
         x += TILE_SIZE;
     }
 
@@ -779,20 +2131,20 @@ fn draw_bottom_status_bar(screen_size_x: i32, _screen_size_y: i32, game: &Game)
 
     if player_underwater {
         x = screen_size_x / 4 - 16;
-        x = puts_proportional(x, y_base, "Air", AIR_COLOR);
+        x = puts_proportional(renderer, x, y_base, text(game.language, Msg::Air), AIR_COLOR);
         x += 8;
 
         const TILE_AIR: u32 = 220;
         const AIR_COLOR: u32 = 0xfffefe54;
         for _ in 0..game.player.turns_remaining_underwater - 1 {
-            draw_tile_by_index(TILE_AIR, x, y_base + 5, AIR_COLOR);
+            draw_tile_by_index(renderer, TILE_AIR, x, y_base + 5, AIR_COLOR);
             x += TILE_SIZE;
         }
 
         const TILE_NO_AIR: u32 = 7;
         const NO_AIR_COLOR: u32 = 0xffa8a800;
         for _ in game.player.turns_remaining_underwater - 1 .. 5 {
-            draw_tile_by_index(TILE_NO_AIR, x, y_base + 5, NO_AIR_COLOR);
+            draw_tile_by_index(renderer, TILE_NO_AIR, x, y_base + 5, NO_AIR_COLOR);
             x += TILE_SIZE;
         }
     }
@@ -803,119 +2155,105 @@ fn draw_bottom_status_bar(screen_size_x: i32, _screen_size_y: i32, game: &Game)
 
     {
         const COLOR: u32 = 0xffa0a0a0;
-        let seen_msg = format!("Level {}: {}% Seen", game.level + 1, percent_seen);
+        let seen_msg = format_text(game.language, Msg::LevelSeen, &[&(game.level + 1), &percent_seen]);
         let (x_min, x_max) = get_horizontal_extents(&seen_msg);
         let x = (screen_size_x - (x_max - x_min)) / 2;
-        puts_proportional(x, y_base, &seen_msg, COLOR);
+        puts_proportional(renderer, x, y_base, &seen_msg, COLOR);
     }
 
     {
         const COLOR: u32 = 0xff36fefe;
         let loot_msg =
             if percent_seen < 100 {
-                format!("Loot {}/?", game.player.gold)
+                format_text(game.language, Msg::LootUnknownTotal, &[&game.player.gold])
             } else {
-                format!("Loot {}/{}", game.player.gold, game.map.total_loot)
+                format_text(game.language, Msg::Loot, &[&game.player.gold, &game.map.total_loot])
             };
         let (x_min, x_max) = get_horizontal_extents(&loot_msg);
         let x = screen_size_x - (8 + (x_max - x_min));
-        puts_proportional(x, y_base, &loot_msg, COLOR);
+        puts_proportional(renderer, x, y_base, &loot_msg, COLOR);
     }
 }
 
-fn draw_top_status_bar(screen_size_x: i32, screen_size_y: i32, game: &Game) {
-    engine::draw_rect(0, screen_size_y - BAR_HEIGHT, screen_size_x, BAR_HEIGHT, BAR_BACKGROUND_COLOR);
+fn draw_top_status_bar(renderer: &mut dyn Renderer, screen_size_x: i32, screen_size_y: i32, game: &Game) {
+    renderer.draw_rect(0, screen_size_y - bar_height(), screen_size_x, bar_height(), BAR_BACKGROUND_COLOR);
 
-    let y_base = screen_size_y - BAR_HEIGHT + 2;
+    let y_base = screen_size_y - bar_height() + 2;
 
     const COLOR: u32 = 0xffffffff; // white
 
     if game.show_help {
-        let msg = format!("Page {} of {}", game.help_page + 1, HELP_MESSAGES.len());
-        let (x_min, x_max) = get_horizontal_extents(&msg);
-        let x = screen_size_x - (8 + (x_max - x_min));
-
-        puts_proportional(x, y_base, &msg, COLOR);
-        puts_proportional(8, y_base, "Press left/right arrow keys to view help, or Esc to close", COLOR);
+        let msg = format_text(game.language, Msg::HelpPageNumber, &[&(game.help_page + 1), &HELP_PAGE_COUNT]);
+        draw_text_layout(renderer, &layout_text(&msg, screen_size_x), 0, y_base, screen_size_x - 8, Align::Right, COLOR);
+        puts_proportional(renderer, 8, y_base, text(game.language, Msg::HelpBarHint), COLOR);
+    } else if game.show_message_log {
+        puts_proportional(renderer, 8, y_base, text(game.language, Msg::MessageLogBarHint), COLOR);
+    } else if game.show_overview {
+        puts_proportional(renderer, 8, y_base, text(game.language, Msg::OverviewBarHint), COLOR);
+    } else if game.options.is_some() {
+        puts_proportional(renderer, 8, y_base, text(game.language, Msg::KeyBindingsBarHint), COLOR);
+    } else if game.look_cursor.is_some() {
+        let msg = format_text(game.language, Msg::LookBarHint, &[&key_names(game.language, &game.key_bindings, Action::Look, "/")]);
+        puts_proportional(renderer, 8, y_base, &msg, COLOR);
     } else {
-        let msg =
-            if game.player.health == 0 {
-                format!("You are dead! Press Ctrl+R for a new game.")
-            } else if game.finished_level {
-                format!("Level {} complete! Move off the edge of the map to advance to the next level.", game.level + 1)
-            } else if game.level == 0 {
-                format!("Welcome to level {}. Collect the gold coins and reveal the whole mansion. (Press ? for help.)", game.level + 1)
-            } else if game.level == 1 {
-                format!("Welcome to level {}. Watch out for the patrolling guard! (Press ? for help.)", game.level + 1)
-            } else {
-                format!("Press ? for help")
-            };
+        let msg = format_text(game.language, Msg::PressForHelp, &[&key_names(game.language, &game.key_bindings, Action::Help, "/")]);
+        draw_text_layout(renderer, &layout_text(&msg, screen_size_x), 0, y_base, screen_size_x - 8, Align::Right, COLOR);
 
-        puts_proportional(8, y_base, &msg, COLOR);
+        if let Some(event) = status_bar_event(game) {
+            puts_proportional(renderer, 8, y_base, text(game.language, Msg::Narration(event)), COLOR);
+        }
     }
 }
 
-static HELP_MESSAGES: &[&str] = &[
-
-// Page 1
-"Disguiser
-
-Press right arrow for hints, or ? to toggle this help
-
-Sneak into mansions, map them, steal all the loot and get out.
-
-The guards cannot be injured! They also cannot cut corners diagonally.
-
-Use the numpad keys to move horizontally, vertically, and diagonally.
-Use numpad 5 to wait. Alternatively use the keys (H J K L Y U B N .),
-or arrow keys with Shift/Ctrl plus Left/Right to move diagonally.
-
-Health is shown on the status bar in the lower left.
-
-A 2021 Seven-day Roguelike Challenge game by James McNeill
-
-Special Thanks: Mendi Carroll, Mike Gaffney
-
-mcneja.github.io
-playtechs.blogspot.com",
-
-// Page 2
-"Hints
-
-Pick up gold coins by moving over them.
+// Narration popups come and go, but what the player needs to do next stays on the
+// status bar for as long as it applies.
 
-Diagonal movement is critical! Guards cannot cut corners, so moving
-diagonally around corners is the key to gaining distance from them.
-
-Guards can only see ahead of themselves.
-
-If a guard sees you and is standing next to you, he will attack!
-
-Bushes, tables, and water can all serve as hiding places. Patrolling guards
-cannot see you when you are hidden. Alert guards (with a question mark
-over their heads) can see you if they are next to you.
-
-High one-way windows allow for quick escapes. Guards can't use them!
-
-Guards can't see as far in the dark outside the mansion."
-];
+fn status_bar_event(game: &Game) -> Option<NarrationEvent> {
+    if game.player.health == 0 {
+        Some(NarrationEvent::PlayerDied)
+    } else if game.finished_level {
+        Some(NarrationEvent::ExitReachable)
+    } else {
+        None
+    }
+}
 
-fn draw_help(screen_size_x: i32, screen_size_y: i32, help_page: usize) {
+fn draw_help(renderer: &mut dyn Renderer, screen_size_x: i32, screen_size_y: i32, language: Language, key_bindings: &KeyBindings, help_page: usize) {
     const BOX_SIZE_X: i32 = 664;
-    const BOX_SIZE_Y: i32 = 470;
+    const BOX_SIZE_Y_MIN: i32 = 470;
     const MARGIN: i32 = 24;
 
     const SCREEN_DARKENING_COLOR: u32 = 0xa0101010;
     const WINDOW_BACKGROUND_COLOR: u32 = 0xff404040;
     const TEXT_COLOR: u32 = 0xffffffff;
 
-    let box_min_x = (screen_size_x - BOX_SIZE_X) / 2;
-    let box_min_y = (screen_size_y - (BAR_HEIGHT + BOX_SIZE_Y)) / 2 + BAR_HEIGHT;
+    // Translations can run longer than the English, so the box grows to fit.
+
+    let moves: Vec<String> = ACTIONS[..8].iter().map(|action| key_names(language, key_bindings, *action, "/")).collect();
+    let keys = |action| key_names(language, key_bindings, action, "/");
+    let help_page_text = format_text(language, Msg::HelpPage(help_page), &[
+        &keys(Action::Help),
+        &moves.join(", "),
+        &keys(Action::Wait),
+        &keys(Action::ToggleGuardVision),
+        &keys(Action::ToggleNoisePreview),
+        &keys(Action::Look),
+        &keys(Action::ZoomIn),
+        &keys(Action::ZoomOut),
+        &keys(Action::ZoomToFit),
+        &keys(Action::ToggleMinimap),
+        &keys(Action::Overview),
+        &keys(Action::MessageLog),
+        &keys(Action::Explore),
+    ]);
+    let help_text = layout_text(&help_page_text, BOX_SIZE_X - 2 * MARGIN);
+    let box_size_y = max(BOX_SIZE_Y_MIN, help_text.height + 2 * MARGIN);
 
-    engine::draw_rect(0, BAR_HEIGHT, screen_size_x, screen_size_y - 2 * BAR_HEIGHT, SCREEN_DARKENING_COLOR);
-    engine::draw_rect(box_min_x, box_min_y, BOX_SIZE_X, BOX_SIZE_Y, WINDOW_BACKGROUND_COLOR);
+    let box_min_x = (screen_size_x - BOX_SIZE_X) / 2;
+    let box_min_y = (screen_size_y - (bar_height() + box_size_y)) / 2 + bar_height();
 
-    let help_msg = HELP_MESSAGES[help_page];
+    renderer.draw_rect(0, bar_height(), screen_size_x, screen_size_y - 2 * bar_height(), SCREEN_DARKENING_COLOR);
+    renderer.draw_rect(box_min_x, box_min_y, BOX_SIZE_X, box_size_y, WINDOW_BACKGROUND_COLOR);
 
-    puts_proportional(box_min_x + MARGIN, box_min_y + BOX_SIZE_Y - (fontdata::LINE_HEIGHT + MARGIN), help_msg, TEXT_COLOR);
+    draw_text_layout(renderer, &help_text, box_min_x + MARGIN, box_min_y + box_size_y - (line_height() + MARGIN), BOX_SIZE_X - 2 * MARGIN, Align::Left, TEXT_COLOR);
 }
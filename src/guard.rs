@@ -1,11 +1,14 @@
-use crate::cell_grid::{CellType, INFINITE_COST, INVALID_REGION, Map, Player, Random};
+use crate::cell_grid::{CellType, INFINITE_COST, INVALID_REGION, ItemKind, Map, Player, Random};
 use crate::color_preset;
 use crate::coord::Coord;
+use crate::dialogue::{Context, Conversation, Dialogue, Disguise};
+use crate::localization::LineSet;
 use crate::speech_bubbles::Popups;
 
 use multiarray::Array2D;
 use rand::Rng;
 use std::cmp::{min, max};
+use std::collections::VecDeque;
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum GuardMode {
@@ -17,6 +20,7 @@ pub enum GuardMode {
     MoveToLastSighting,
     MoveToLastSound,
     MoveToGuardShout,
+    SearchForMissingLoot,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -51,14 +55,43 @@ pub struct Guard {
     // Patrol
     pub region_goal: usize,
     pub region_prev: usize,
+
+    // Recent sightings and sounds
+    pub memory: GuardMemory,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryKind {
+    SawThief,
+    HeardThief,
+    HeardGuard,
+    Anomaly,
+}
+
+#[derive(Clone, Copy)]
+pub struct MemoryEvent {
+    pub kind: MemoryKind,
+    pub pos: Coord,
+    pub turn: usize,
+}
+
+pub struct GuardMemory {
+    events: VecDeque<MemoryEvent>, // oldest first
+    pub chased_thief: bool, // has this guard chased the thief at any point on this level?
+    loot_known: Vec<Coord>, // coins this guard expects to find on its rounds
 }
 
+const MEMORY_EVENTS_MAX: usize = 8;
+
+// How long a guard stays on edge after seeing or hearing the thief
+const MEMORY_RECENT_TURNS: usize = 20;
+
 struct Shout {
     pos_shouter: Coord, // where is the person shouting?
     pos_target: Coord, // where are they reporting the player is?
 }
 
-pub fn guard_act_all(random: &mut Random, see_all: bool, popups: &mut Popups, lines: &mut Lines, map: &mut Map, player: &mut Player) {
+pub fn guard_act_all(random: &mut Random, see_all: bool, turn: usize, popups: &mut Popups, dialogue: &mut Dialogue, map: &mut Map, player: &mut Player) {
 
     // Mark if we heard a guard last turn, and clear the speaking flag.
 
@@ -66,21 +99,136 @@ pub fn guard_act_all(random: &mut Random, see_all: bool, popups: &mut Popups, li
         guard.pre_turn();
     }
 
+    // Note each guard's situation as of the start of the turn, for choosing what they say.
+
+    let mut speech = Speech {
+        dialogue,
+        contexts: (0..map.guards.len()).map(|i_guard| dialogue_context(map, player, turn, i_guard)).collect(),
+        popups,
+        see_all,
+        turn,
+    };
+
     // Update each guard for this turn.
 
     let mut guards = map.guards.split_off(0);
     let mut shouts: Vec<Shout> = Vec::new();
+    let mut speeches: Vec<(usize, LineSet)> = Vec::new();
 
-    for mut guard in guards.drain(..) {
-        guard.act(random, see_all, popups, lines, player, map, &mut shouts);
+    for (i_guard, mut guard) in guards.drain(..).enumerate() {
+        if let Some(line_set) = guard.act(random, turn, player, map, &mut shouts) {
+            speeches.push((i_guard, line_set));
+        }
         map.guards.push(guard);
     }
 
+    // Say something to indicate state changes
+
+    for (i_guard, line_set) in speeches {
+        let line = speech.dialogue.line(random, line_set, &speech.contexts[i_guard]);
+        let guard = &mut map.guards[i_guard];
+        if line_set == LineSet::Damage {
+            speech.popups.damage(guard.pos, line);
+        } else {
+            guard.say(speech.popups, player, speech.see_all, line);
+        }
+    }
+
     // Process shouts
 
     for shout in &shouts {
         alert_nearby_guards(map, &shout);
     }
+
+    // Patrolling guards chat when they meet
+
+    converse(random, &mut speech, map, player);
+}
+
+// What guards draw on to pick their lines, and where the lines go
+
+struct Speech<'a> {
+    dialogue: &'a mut Dialogue,
+    contexts: Vec<Context>, // indexed by guard
+    popups: &'a mut Popups,
+    see_all: bool,
+    turn: usize,
+}
+
+const NEAR_GUARDS_DIST_SQUARED: i32 = 64;
+
+fn dialogue_context(map: &Map, player: &Player, turn: usize, i_guard: usize) -> Context {
+    let guard = &map.guards[i_guard];
+    Context {
+        guard_kind: guard.kind,
+        wary: guard.memory.chased_thief,
+        alert: guard.mode != GuardMode::Patrol || guard.memory.recently_alerted(turn),
+        disguise: match player.disguise {
+            None => Disguise::Plain,
+            Some(kind) if kind == guard.kind => Disguise::AsSameKind,
+            Some(_) => Disguise::AsOtherKind,
+        },
+        turn,
+        near_guards: map.guards.iter().enumerate().any(|(i, other)| i != i_guard && (other.pos - guard.pos).length_squared() <= NEAR_GUARDS_DIST_SQUARED),
+    }
+}
+
+// Guards within a couple of cells of each other may strike up a conversation,
+// which goes on one line per turn while they stay in sight of each other.
+
+const CONVERSATION_START_DIST_SQUARED: i32 = 8;
+const CONVERSATION_DIST_SQUARED_MAX: i32 = 25;
+const CONVERSATION_INTERVAL_TURNS: usize = 40;
+
+fn converse(random: &mut Random, speech: &mut Speech, map: &mut Map, player: &Player) {
+    let dialogue = &mut *speech.dialogue;
+
+    if let Some(mut conversation) = dialogue.conversation.take() {
+        let i_speaker = conversation.speakers[conversation.line_index % 2];
+        let i_listener = conversation.speakers[1 - conversation.line_index % 2];
+        if can_converse(map, i_speaker, i_listener, CONVERSATION_DIST_SQUARED_MAX) {
+            map.guards[i_speaker].say(speech.popups, player, speech.see_all, conversation.lines[conversation.line_index]);
+            conversation.line_index += 1;
+            if conversation.line_index < conversation.lines.len() {
+                dialogue.conversation = Some(conversation);
+            }
+        }
+        return;
+    }
+
+    if speech.turn < dialogue.turn_next_conversation {
+        return;
+    }
+
+    for i_guard0 in 0..map.guards.len() {
+        for i_guard1 in i_guard0 + 1 .. map.guards.len() {
+            if !can_converse(map, i_guard0, i_guard1, CONVERSATION_START_DIST_SQUARED) || !random.gen_bool(0.25) {
+                continue;
+            }
+
+            // Either guard may start; the exchange is chosen to suit whoever does.
+
+            let speakers = if random.gen() {[i_guard0, i_guard1]} else {[i_guard1, i_guard0]};
+            if let Some(lines) = dialogue.choose_exchange(random, &speech.contexts[speakers[0]]) {
+                map.guards[speakers[0]].say(speech.popups, player, speech.see_all, lines[0]);
+                dialogue.conversation = Some(Conversation { speakers, lines, line_index: 1 });
+                dialogue.turn_next_conversation = speech.turn + CONVERSATION_INTERVAL_TURNS;
+                return;
+            }
+        }
+    }
+}
+
+fn can_converse(map: &Map, i_guard0: usize, i_guard1: usize, dist_squared_max: i32) -> bool {
+    let (guard0, guard1) = match (map.guards.get(i_guard0), map.guards.get(i_guard1)) {
+        (Some(guard0), Some(guard1)) => (guard0, guard1),
+        _ => return false,
+    };
+
+    guard0.mode == GuardMode::Patrol && guard1.mode == GuardMode::Patrol &&
+    !guard0.speaking && !guard1.speaking &&
+    (guard1.pos - guard0.pos).length_squared() <= dist_squared_max &&
+    line_of_sight(map, guard0.pos, guard1.pos)
 }
 
 fn alert_nearby_guards(map: &mut Map, shout: &Shout) {
@@ -91,6 +239,113 @@ fn alert_nearby_guards(map: &mut Map, shout: &Shout) {
     }
 }
 
+pub fn new_guard_memory() -> GuardMemory {
+    GuardMemory {
+        events: VecDeque::with_capacity(MEMORY_EVENTS_MAX),
+        chased_thief: false,
+        loot_known: Vec::new(),
+    }
+}
+
+impl GuardMemory {
+    pub fn remember(&mut self, kind: MemoryKind, pos: Coord, turn: usize) {
+        // Repeated observations of the same kind on the same turn replace each other.
+
+        if let Some(event) = self.events.back_mut() {
+            if event.kind == kind && event.turn == turn {
+                event.pos = pos;
+                return;
+            }
+        }
+
+        if self.events.len() >= MEMORY_EVENTS_MAX {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(MemoryEvent { kind, pos, turn });
+    }
+
+    pub fn recently_alerted(&self, turn: usize) -> bool {
+        self.events.iter().any(|event|
+            event.kind != MemoryKind::HeardGuard &&
+            event.turn + MEMORY_RECENT_TURNS >= turn)
+    }
+
+    // Take note of all the loot lying in the patrolled parts of the map.
+
+    pub fn learn_loot(&mut self, map: &Map) {
+        self.loot_known = map.items
+            .iter()
+            .filter(|item| item.kind == ItemKind::Coin)
+            .filter(|item| map.cells[[item.pos.0 as usize, item.pos.1 as usize]].region != INVALID_REGION)
+            .map(|item| item.pos)
+            .collect();
+    }
+
+    // Compare the loot remembered in the region containing pos against what is
+    // actually there. Missing loot is forgotten so it is only noticed once; the
+    // position of one of the missing coins is returned.
+
+    pub fn check_for_missing_loot(&mut self, map: &Map, pos: Coord) -> Option<Coord> {
+        let region = map.cells[[pos.0 as usize, pos.1 as usize]].region;
+        if region == INVALID_REGION {
+            return None;
+        }
+
+        let mut pos_missing = None;
+
+        self.loot_known.retain(|&pos_loot| {
+            if map.cells[[pos_loot.0 as usize, pos_loot.1 as usize]].region != region {
+                return true;
+            }
+            if map.items.iter().any(|item| item.kind == ItemKind::Coin && item.pos == pos_loot) {
+                return true;
+            }
+            pos_missing = Some(pos_loot);
+            false
+        });
+
+        pos_missing
+    }
+
+    // Extrapolate from the two most recent sightings to guess where the thief is now.
+
+    pub fn predicted_thief_pos(&self, map: &Map, turn: usize) -> Option<Coord> {
+        let mut sightings = self.events.iter().rev().filter(|event| event.kind == MemoryKind::SawThief);
+        let last = sightings.next()?;
+        let prev = match sightings.next() {
+            Some(prev) => prev,
+            None => return Some(last.pos),
+        };
+
+        let d = last.pos - prev.pos;
+        let dir = Coord(d.0.signum(), d.1.signum());
+        if dir == Coord(0, 0) {
+            return Some(last.pos);
+        }
+
+        // Assume the thief keeps moving one square per turn in the same direction
+        // until running into something.
+
+        let steps = max(1, turn - last.turn) + 1;
+        let mut pos = last.pos;
+        for _ in 0..steps {
+            let pos_next = pos + dir;
+            if pos_next.0 < 0 || pos_next.1 < 0 ||
+               pos_next.0 >= map.cells.extents()[0] as i32 ||
+               pos_next.1 >= map.cells.extents()[1] as i32 {
+                break;
+            }
+            if map.guard_move_cost(pos, pos_next) == INFINITE_COST {
+                break;
+            }
+            pos = pos_next;
+        }
+
+        Some(pos)
+    }
+}
+
 fn pos_next_best(map: &Map, distance_field: &Array2D<usize>, pos_from: Coord) -> Coord {
     let mut cost_best = INFINITE_COST;
     let mut pos_best = pos_from;
@@ -128,79 +383,32 @@ fn pos_next_best(map: &Map, distance_field: &Array2D<usize>, pos_from: Coord) ->
     pos_best
 }
 
-struct LineIter {
-    lines: &'static [&'static str],
-    line_index: usize,
-}
-
-impl LineIter {
-    fn new(lines: &'static [&'static str]) -> LineIter {
-        LineIter { lines, line_index: 0 }
-    }
-
-    fn next(&mut self) -> &'static str {
-        let s = self.lines[self.line_index];
-        self.line_index = (self.line_index + 1) % self.lines.len();
-        s
-    }
-}
-
-pub struct Lines {
-    see: LineIter,
-    see_disguised: LineIter,
-    hear: LineIter,
-    hear_guard: LineIter,
-    chase: LineIter,
-    investigate: LineIter,
-    end_chase: LineIter,
-    end_investigate: LineIter,
-    done_looking: LineIter,
-    done_seeing_disguised: LineIter,
-    done_listening: LineIter,
-    damage: LineIter,
-}
-
-pub fn new_lines() -> Lines {
-    Lines {
-        see: LineIter::new(SEE_LINES),
-        see_disguised: LineIter::new(SEE_DISGUISED_LINES),
-        hear: LineIter::new(HEAR_LINES),
-        hear_guard: LineIter::new(HEAR_GUARD_LINES),
-        chase: LineIter::new(CHASE_LINES),
-        investigate: LineIter::new(INVESTIGATE_LINES),
-        end_chase: LineIter::new(END_CHASE_LINES),
-        end_investigate: LineIter::new(END_INVESTIGATION_LINES),
-        done_looking: LineIter::new(DONE_LOOKING_LINES),
-        done_seeing_disguised: LineIter::new(DONE_SEEING_DISGUISED_LINES),
-        done_listening: LineIter::new(DONE_LISTENING_LINES),
-        damage: LineIter::new(DAMAGE_LINES),
-    }
-}
-
-fn lines_for_state_change(lines: &mut Lines, mode_prev: GuardMode, mode_next: GuardMode) -> Option<&mut LineIter> {
+fn lines_for_state_change(mode_prev: GuardMode, mode_next: GuardMode) -> Option<LineSet> {
     if mode_next == mode_prev {
         None
     } else {
         match mode_next {
             GuardMode::Patrol => {
                 match mode_prev {
-                    GuardMode::Look => Some(&mut lines.done_looking),
-                    GuardMode::LookAtDisguised => Some(&mut lines.done_seeing_disguised),
-                    GuardMode::Listen => Some(&mut lines.done_listening),
+                    GuardMode::Look => Some(LineSet::DoneLooking),
+                    GuardMode::LookAtDisguised => Some(LineSet::DoneSeeingDisguised),
+                    GuardMode::Listen => Some(LineSet::DoneListening),
                     GuardMode::MoveToLastSound |
-                    GuardMode::MoveToGuardShout => Some(&mut lines.end_investigate),
-                    GuardMode::MoveToLastSighting => Some(&mut lines.end_chase),
+                    GuardMode::MoveToGuardShout => Some(LineSet::EndInvestigation),
+                    GuardMode::MoveToLastSighting => Some(LineSet::EndChase),
+                    GuardMode::SearchForMissingLoot => Some(LineSet::EndSearch),
                     _ => None
                 }
             },
-            GuardMode::Look => Some(&mut lines.see),
-            GuardMode::LookAtDisguised => Some(&mut lines.see_disguised),
-            GuardMode::Listen => Some(&mut lines.hear),
+            GuardMode::Look => Some(LineSet::See),
+            GuardMode::LookAtDisguised => Some(LineSet::SeeDisguised),
+            GuardMode::Listen => Some(LineSet::Hear),
             GuardMode::ChaseVisibleTarget =>
-                if mode_prev != GuardMode::MoveToLastSighting {Some(&mut lines.chase)} else {None},
+                if mode_prev != GuardMode::MoveToLastSighting {Some(LineSet::Chase)} else {None},
             GuardMode::MoveToLastSighting => None,
-            GuardMode::MoveToLastSound => Some(&mut lines.investigate),
-            GuardMode::MoveToGuardShout => Some(&mut lines.hear_guard),
+            GuardMode::MoveToLastSound => Some(LineSet::Investigate),
+            GuardMode::MoveToGuardShout => Some(LineSet::HearGuard),
+            GuardMode::SearchForMissingLoot => Some(LineSet::NoticeMissingLoot),
         }
     }
 }
@@ -223,9 +431,12 @@ fn hear_guard(&mut self, pos_target: Coord) {
     self.heard_guard_pos = pos_target;
 }
 
-fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines: &mut Lines, player: &mut Player, map: &Map, shouts: &mut Vec<Shout>) {
+// Returns the set of lines to say something from, if anything
+
+fn act(&mut self, random: &mut Random, turn: usize, player: &mut Player, map: &Map, shouts: &mut Vec<Shout>) -> Option<LineSet> {
 
     let mode_prev = self.mode;
+    let mut speech = None;
 
     // If we're already vigilant and we can see the player at the start of our turn,
     // go directly into chase mode so we can immediately move toward the player.
@@ -238,7 +449,18 @@ fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines
 
     // Hearing-based state changes
 
-    self.update_state_based_on_hearing(random, player);
+    self.update_state_based_on_hearing(random, turn, player);
+
+    // Patrolling guards notice when loot has gone missing from the area they're in
+
+    if self.mode == GuardMode::Patrol {
+        if let Some(pos_loot) = self.memory.check_for_missing_loot(map, self.pos) {
+            self.memory.remember(MemoryKind::Anomaly, pos_loot, turn);
+            self.mode = GuardMode::SearchForMissingLoot;
+            self.mode_timeout = self.alert_timeout(random) + 2;
+            self.goal = pos_loot;
+        }
+    }
 
     // Pass time in the current mode
 
@@ -259,7 +481,7 @@ fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines
                 self.dir = update_dir(self.dir, self.goal - self.pos);
                 if mode_prev == GuardMode::ChaseVisibleTarget {
                     if !player.damaged_last_turn {
-                        popups.damage(self.pos, lines.damage.next());
+                        speech = Some(LineSet::Damage);
                     }
                     player.apply_damage(1);
                 }
@@ -274,6 +496,18 @@ fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines
                 self.mode_timeout -= 1;
             }
 
+            if self.mode_timeout == 0 {
+                self.mode = GuardMode::Patrol;
+                self.setup_goal_region(random, map);
+            }
+        },
+        GuardMode::SearchForMissingLoot => {
+            // Once at the scene of the theft, look around.
+            if !self.move_toward_goal(map, player) {
+                self.mode_timeout -= 1;
+                self.dir = Coord(-self.dir.1, self.dir.0);
+            }
+
             if self.mode_timeout == 0 {
                 self.mode = GuardMode::Patrol;
                 self.setup_goal_region(random, map);
@@ -287,26 +521,34 @@ fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines
         let disguised = player.is_appropriately_disguised();
         if self.mode == GuardMode::Patrol && (disguised || !self.adjacent_to(player.pos)) {
             self.mode = if disguised {GuardMode::LookAtDisguised} else {GuardMode::Look};
-            self.mode_timeout = random.gen_range(3..6);
+            self.mode_timeout = self.alert_timeout(random);
         } else {
             self.mode = GuardMode::ChaseVisibleTarget;
             self.goal = player.pos;
             self.dir = update_dir(self.dir, self.goal - self.pos);
         }
+        if !disguised || self.mode == GuardMode::ChaseVisibleTarget {
+            self.memory.remember(MemoryKind::SawThief, player.pos, turn);
+        }
     } else if self.mode == GuardMode::ChaseVisibleTarget {
+        // Head for where the thief was likely going, not where they were last seen.
         self.mode = GuardMode::MoveToLastSighting;
         self.mode_timeout = 3;
-        self.goal = player.pos;
+        self.goal = self.memory.predicted_thief_pos(map, turn).unwrap_or(player.pos);
+    }
+
+    if self.mode == GuardMode::ChaseVisibleTarget {
+        self.memory.chased_thief = true;
     }
 
     // Clear heard-thief flag
 
     self.heard_thief = false;
 
-    // Say something to indicate state changes
+    // Pick something to say about state changes
 
-    if let Some(line_iter) = lines_for_state_change(lines, mode_prev, self.mode) {
-        self.say(popups, player, see_all, line_iter.next());
+    if speech.is_none() {
+        speech = lines_for_state_change(mode_prev, self.mode);
     }
 
     // Shout to allies on entry to ChaseVisibleTarget
@@ -314,9 +556,17 @@ fn act(&mut self, random: &mut Random, see_all: bool, popups: &mut Popups, lines
     if self.mode == GuardMode::ChaseVisibleTarget && mode_prev != GuardMode::ChaseVisibleTarget {
         shouts.push(Shout{pos_shouter: self.pos, pos_target: player.pos});
     }
+
+    // Raise the alarm on discovering a theft
+
+    if self.mode == GuardMode::SearchForMissingLoot && mode_prev != GuardMode::SearchForMissingLoot {
+        shouts.push(Shout{pos_shouter: self.pos, pos_target: self.goal});
+    }
+
+    speech
 }
 
-fn update_state_based_on_hearing(&mut self, random: &mut Random, player: &Player) {
+fn update_state_based_on_hearing(&mut self, random: &mut Random, turn: usize, player: &Player) {
     if self.mode == GuardMode::ChaseVisibleTarget {
         return;
     }
@@ -325,18 +575,25 @@ fn update_state_based_on_hearing(&mut self, random: &mut Random, player: &Player
         self.mode = GuardMode::MoveToGuardShout;
         self.mode_timeout = random.gen_range(2..6);
         self.goal = self.heard_guard_pos;
+        self.memory.remember(MemoryKind::HeardGuard, self.heard_guard_pos, turn);
     }
 
     if self.heard_thief {
+        // A guard who has recently seen or heard the thief goes to investigate
+        // right away instead of stopping to listen.
+
+        let on_edge = self.memory.recently_alerted(turn);
+        self.memory.remember(MemoryKind::HeardThief, player.pos, turn);
+
         if self.adjacent_to(player.pos) {
             self.mode = GuardMode::ChaseVisibleTarget;
             self.goal = player.pos;
-        } else if self.mode == GuardMode::Patrol {
+        } else if self.mode == GuardMode::Patrol && !on_edge {
             self.mode = GuardMode::Listen;
-            self.mode_timeout = random.gen_range(3..6);
+            self.mode_timeout = self.alert_timeout(random);
         } else {
             self.mode = GuardMode::MoveToLastSound;
-            self.mode_timeout = random.gen_range(3..6);
+            self.mode_timeout = self.alert_timeout(random);
             self.goal = player.pos;
         }
     }
@@ -417,12 +674,56 @@ pub fn can_see(&self, map: &Map, pos: Coord, hidden: bool) -> bool {
     true
 }
 
+// Guards who have chased the thief stay wary for the rest of the level: they see
+// farther while patrolling and stay alert longer.
+
+fn alert_timeout(&self, random: &mut Random) -> usize {
+    if self.memory.chased_thief {random.gen_range(5..8)} else {random.gen_range(3..6)}
+}
+
+// Farthest distance, in cells, at which this guard can currently see anything
+
+pub fn sight_radius(&self) -> i32 {
+    let cutoff = max(self.cutoff_lit(), self.cutoff_unlit());
+    (cutoff as f32).sqrt() as i32 + 1
+}
+
+// Call f for every cell this guard could see an unhidden thief in, along with
+// whether the cell is lit. Follows the same rules as can_see().
+
+pub fn for_each_visible_cell<F>(&self, map: &Map, mut f: F) where F: FnMut(Coord, bool) {
+    let radius = self.sight_radius();
+
+    let pos_min = Coord(max(0, self.pos.0 - radius), max(0, self.pos.1 - radius));
+    let pos_max = Coord(min(map.cells.extents()[0] as i32, self.pos.0 + radius + 1), min(map.cells.extents()[1] as i32, self.pos.1 + radius + 1));
+
+    for x in pos_min.0 .. pos_max.0 {
+        for y in pos_min.1 .. pos_max.1 {
+            let pos = Coord(x, y);
+            if pos == self.pos {
+                continue;
+            }
+            if self.can_see(map, pos, false) {
+                f(pos, map.cells[[x as usize, y as usize]].lit);
+            }
+        }
+    }
+}
+
 fn cutoff_lit(&self) -> i32 {
-    if self.mode == GuardMode::Patrol || self.mode == GuardMode::LookAtDisguised {40} else {75}
+    if self.mode == GuardMode::Patrol || self.mode == GuardMode::LookAtDisguised {
+        if self.memory.chased_thief {55} else {40}
+    } else {
+        75
+    }
 }
 
 fn cutoff_unlit(&self) -> i32 {
-    if self.mode == GuardMode::Patrol || self.mode == GuardMode::LookAtDisguised {3} else {33}
+    if self.mode == GuardMode::Patrol || self.mode == GuardMode::LookAtDisguised {
+        if self.memory.chased_thief {8} else {3}
+    } else {
+        33
+    }
 }
 
 fn sight_cutoff(&self, lit_target: bool) -> i32 {
@@ -451,9 +752,9 @@ pub fn initial_dir(&self, map: &Map) -> Coord
         return self.dir;
     }
 
-    let distance_field = map.compute_distances_to_region(self.region_goal);
+    let distance_field = map.distances_to_region(self.region_goal);
 
-    let pos_next = pos_next_best(map, &distance_field, self.pos);
+    let pos_next = pos_next_best(map, distance_field, self.pos);
 
     update_dir(self.dir, pos_next - self.pos)
 }
@@ -463,22 +764,23 @@ fn move_toward_region(&mut self, map: &Map, player: &Player) -> bool {
         return false;
     }
 
-    let distance_field = map.compute_distances_to_region(self.region_goal);
+    let distance_field = map.distances_to_region(self.region_goal);
 
-    let pos_next = pos_next_best(map, &distance_field, self.pos);
+    let pos_next = pos_next_best(map, distance_field, self.pos);
 
     if player.pos == pos_next {
         return true;
     }
 
     self.dir = update_dir(self.dir, pos_next - self.pos);
+    self.has_moved = pos_next != self.pos;
     self.pos = pos_next;
 
     false
 }
 
 fn move_toward_goal(&mut self, map: &Map, player: &Player) -> bool {
-    let dist_field = map.compute_distances_to_position(self.goal);
+    let dist_field = map.compute_distances_to_position_near(self.goal, self.pos);
 
     let pos_next = pos_next_best(map, &dist_field, self.pos);
     if pos_next == self.pos {
@@ -492,6 +794,7 @@ fn move_toward_goal(&mut self, map: &Map, player: &Player) -> bool {
     }
 
     self.pos = pos_next;
+    self.has_moved = true;
     true
 }
 
@@ -530,6 +833,12 @@ pub fn update_dir(dir_forward: Coord, dir_aim: Coord) -> Coord {
 }
 
 fn line_of_sight(map: &Map, from: Coord, to: Coord) -> bool {
+    line_is_clear(from, to, |x, y| map.blocks_sight(x, y))
+}
+
+// Step along the cells between from and to (exclusive) and check none of them block.
+
+pub fn line_is_clear<F>(from: Coord, to: Coord, blocks: F) -> bool where F: Fn(i32, i32) -> bool {
     let mut x = from.0;
     let mut y = from.1;
 
@@ -558,7 +867,7 @@ fn line_of_sight(map: &Map, from: Coord, to: Coord) -> bool {
             error += ay;
         }
 
-        if map.blocks_sight(x, y) {
+        if blocks(x, y) {
             return false;
         }
 
@@ -567,152 +876,3 @@ fn line_of_sight(map: &Map, from: Coord, to: Coord) -> bool {
 
     true
 }
-
-static SEE_LINES: &[&str] = &[
-    "Who goes there?",
-    "Huh?",
-    "What?",
-    "Wait...",
-    "Who's that?",
-    "Hey...",
-    "Hmm...",
-    "What moved?",
-    "Did that shadow move?",
-    "I see something...",
-    "Hello?",
-];
-
-static SEE_DISGUISED_LINES: &[&str] = &[
-    "Who are you?",
-    "You don't look familiar!",
-    "Do I know you?",
-    "Wait...",
-    "Hey...",
-    "Let me see your face...",
-    "Do you belong here?",
-    "You are...?",
-    "Are you new here?",
-];
-
-static HEAR_LINES: &[&str] = &[
-    "Huh?",
-    "What?",
-    "Hark!",
-    "A noise...",
-    "I heard something.",
-    "Hmm...",
-    "Who goes there?",
-    "What's that noise?",
-    "I hear something...",
-    "Hello?",
-];
-
-static HEAR_GUARD_LINES: &[&str] = &[
-    "Where?",
-    "I'm coming!",
-    "Here I come!",
-    "To arms!",
-    "Where is he?",
-];
-
-static CHASE_LINES: &[&str] = &[
-    "Halt!",
-    "Hey!",
-    "Aha!",
-    "I see you!",
-    "I'm coming!",
-    "I'll get you!",
-    "Just you wait...",
-    "You won't get away!",
-    "Oh no you don't...",
-    "Get him!",
-    "After him!",
-    "Thief!",
-];
-
-static INVESTIGATE_LINES: &[&str] = &[
-    "That noise again...",
-    "I heard it again!",
-    "Someone's there!",
-    "Who could that be?",
-    "There it is again!",
-    "What was that?",
-    "Better check it out...",
-    "What keeps making those noises?",
-    "That better be rats!",
-    "Again?",
-];
-
-static END_CHASE_LINES: &[&str] = &[
-    "(huff, huff)",
-    "Where did he go?",
-    "Lost him!",
-    "Gone!",
-    "Come back!",
-    "Argh!",
-    "He's not coming back.",
-    "Blast!",
-    "Next time!",
-];
-
-static END_INVESTIGATION_LINES: &[&str] = &[
-    "Guess it was nothing.",
-    "Wonder what it was?",
-    "Better get back.",
-    "It's quiet now.",
-    "This is where I heard it...",
-    "Nothing, now.",
-];
-
-static DONE_LOOKING_LINES: &[&str] = &[
-    "Must have been rats.",
-    "Too much coffee!",
-    "I've got the jitters.",
-    "Probably nothing.",
-    "I thought I saw something.",
-    "Oh well.",
-    "Nothing.",
-    "Can't see it now.",
-    "I've been up too long.",
-    "Seeing things, I guess.",
-    "Hope it wasn't anything.",
-    "Did I imagine that?",
-];
-
-static DONE_SEEING_DISGUISED_LINES: &[&str] = &[
-    "Who was that?",
-    "Huh...",
-    "I wonder who that was?",
-    "Oh well.",
-    "I'm seeing things.",
-    "I've been up too long.",
-    "Seeing things, I guess.",
-    "Probably new here.",
-    "Better get back to it.",
-    "Did I imagine that?",
-    "Should I tell the boss?",
-];
-
-static DONE_LISTENING_LINES: &[&str] = &[
-    "Must have been rats.",
-    "Too much coffee!",
-    "I've got the jitters.",
-    "Probably nothing.",
-    "I thought I heard something.",
-    "Oh well.",
-    "Nothing.",
-    "Can't hear it now.",
-    "I've been up too long.",
-    "Hearing things, I guess.",
-    "Hope it wasn't anything.",
-    "Did I imagine that?",
-];
-
-static DAMAGE_LINES: &[&str] = &[
-    "Oof!",
-    "Krak!",
-    "Pow!",
-    "Urk!",
-    "Smack!",
-    "Bif!",
-];
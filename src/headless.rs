@@ -0,0 +1,100 @@
+use crate::game::{self, Game};
+use crate::render::{Image, SoftwareRenderer, new_software_renderer};
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// Running the game without a browser. Frames are drawn by the software renderer
+// from the same tile and font images as the web build, and can be saved as PNG
+// files; the golden-image tests in tests/golden.rs drive the game this way.
+
+pub struct Headless {
+    game: Game,
+    renderer: SoftwareRenderer,
+}
+
+// Animations are run to completion before each frame is drawn, in steps of this
+// many milliseconds, so that frames depend only on the seed and the input.
+const SETTLE_TICK_MS: f32 = 100.0;
+const SETTLE_TICKS: usize = 4;
+
+// Textures in the order game.js loads them
+
+const TEXTURE_FILES: [&str; 2] = ["tiles.png", "font.png"];
+
+pub fn load_textures(dir: &Path) -> Result<Vec<Image>, String> {
+    TEXTURE_FILES.iter().map(|file| load_png(&dir.join(file))).collect()
+}
+
+pub fn new_headless(seed: u64, screen_size_x: i32, screen_size_y: i32, textures: Vec<Image>) -> Headless {
+    let mut game = game::new_game(seed);
+    game::on_resize(&mut game, screen_size_x, screen_size_y);
+
+    Headless {
+        game,
+        renderer: new_software_renderer(screen_size_x, screen_size_y, textures),
+    }
+}
+
+impl Headless {
+    pub fn key_down(&mut self, key: i32, ctrl_key_down: bool, shift_key_down: bool) {
+        game::on_key_down(&mut self.game, key, ctrl_key_down, shift_key_down);
+        game::play_queued_sounds(&mut self.game);
+    }
+
+    pub fn draw(&mut self) -> &Image {
+        for _ in 0..SETTLE_TICKS {
+            game::on_tick(&mut self.game, SETTLE_TICK_MS);
+        }
+
+        let screen_size_x = self.renderer.screen.size_x;
+        let screen_size_y = self.renderer.screen.size_y;
+
+        self.renderer.clear();
+        game::on_draw(&mut self.game, &mut self.renderer, screen_size_x, screen_size_y);
+        &self.renderer.screen
+    }
+}
+
+// PNG files. Images of any color type are converted to 8-bit RGBA on loading.
+
+pub fn load_png(path: &Path) -> Result<Image, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+
+    let file = File::open(path).map_err(|e| error(&e))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| error(&e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| error(&e))?;
+
+    let channels = info.color_type.samples();
+    let pixels = buf[..info.buffer_size()].chunks_exact(channels).map(|c| {
+        let [r, g, b, a] = match info.color_type {
+            png::ColorType::Grayscale => [c[0], c[0], c[0], 0xff],
+            png::ColorType::GrayscaleAlpha => [c[0], c[0], c[0], c[1]],
+            png::ColorType::Rgb => [c[0], c[1], c[2], 0xff],
+            _ => [c[0], c[1], c[2], c[3]],
+        };
+        u32::from_le_bytes([r, g, b, a])
+    }).collect();
+
+    Ok(Image {
+        size_x: info.width as i32,
+        size_y: info.height as i32,
+        pixels,
+    })
+}
+
+pub fn save_png(path: &Path, image: &Image) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+
+    let file = File::create(path).map_err(|e| error(&e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.size_x as u32, image.size_y as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    let mut writer = encoder.write_header().map_err(|e| error(&e))?;
+    writer.write_image_data(&image.to_rgba()).map_err(|e| error(&e))
+}
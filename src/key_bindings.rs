@@ -0,0 +1,253 @@
+use crate::coord::Coord;
+use crate::engine;
+
+// Things the player can do with a single key press. The numeric values are what
+// get saved, so only add new actions at the end.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    MoveN = 0,
+    MoveNE = 1,
+    MoveE = 2,
+    MoveSE = 3,
+    MoveS = 4,
+    MoveSW = 5,
+    MoveW = 6,
+    MoveNW = 7,
+    Wait = 8,
+    Help = 9,
+    ToggleMessages = 10,
+    ToggleGuardVision = 11,
+    ToggleNoisePreview = 12,
+    Look = 13,
+    Explore = 14,
+    ZoomIn = 15,
+    ZoomOut = 16,
+    ZoomToFit = 17,
+    ToggleMinimap = 18,
+    Overview = 19,
+    MessageLog = 20,
+}
+
+pub const ACTIONS: [Action; 21] = [
+    Action::MoveN,
+    Action::MoveNE,
+    Action::MoveE,
+    Action::MoveSE,
+    Action::MoveS,
+    Action::MoveSW,
+    Action::MoveW,
+    Action::MoveNW,
+    Action::Wait,
+    Action::Help,
+    Action::ToggleMessages,
+    Action::ToggleGuardVision,
+    Action::ToggleNoisePreview,
+    Action::Look,
+    Action::Explore,
+    Action::ZoomIn,
+    Action::ZoomOut,
+    Action::ZoomToFit,
+    Action::ToggleMinimap,
+    Action::Overview,
+    Action::MessageLog,
+];
+
+// Saved bindings record which preset they were based on, so keep these values too.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Preset {
+    Standard = 0,
+    ViKeys = 1,
+    Numpad = 2,
+    Wasd = 3,
+}
+
+pub const PRESETS: [Preset; 4] = [
+    Preset::Standard,
+    Preset::ViKeys,
+    Preset::Numpad,
+    Preset::Wasd,
+];
+
+pub struct KeyBindings {
+    pub preset: Preset, // the defaults these bindings started from
+    bindings: Vec<(i32, Action)>, // (key code, action); each key appears at most once
+}
+
+pub fn new_key_bindings(preset: Preset) -> KeyBindings {
+    let mut key_bindings = KeyBindings { preset, bindings: Vec::new() };
+
+    // Arrow keys and the non-movement actions are the same in every preset.
+
+    key_bindings.bind(engine::KEY_UP, Action::MoveN);
+    key_bindings.bind(engine::KEY_RIGHT, Action::MoveE);
+    key_bindings.bind(engine::KEY_DOWN, Action::MoveS);
+    key_bindings.bind(engine::KEY_LEFT, Action::MoveW);
+    key_bindings.bind(engine::KEY_SLASH, Action::Help);
+    key_bindings.bind(engine::KEY_SPACE, Action::ToggleMessages);
+    key_bindings.bind(engine::KEY_EQUAL, Action::ZoomIn);
+    key_bindings.bind(engine::KEY_NUMPAD_ADD, Action::ZoomIn);
+    key_bindings.bind(engine::KEY_MINUS, Action::ZoomOut);
+    key_bindings.bind(engine::KEY_NUMPAD_SUBTRACT, Action::ZoomOut);
+    key_bindings.bind(engine::KEY_0, Action::ZoomToFit);
+    key_bindings.bind(engine::KEY_M, Action::ToggleMinimap);
+    key_bindings.bind(engine::KEY_TAB, Action::Overview);
+    key_bindings.bind(engine::KEY_P, Action::MessageLog);
+
+    let uses_vi_keys = preset == Preset::Standard || preset == Preset::ViKeys;
+    let uses_numpad = preset == Preset::Standard || preset == Preset::Numpad;
+
+    if uses_numpad {
+        key_bindings.bind(engine::KEY_NUMPAD8, Action::MoveN);
+        key_bindings.bind(engine::KEY_NUMPAD9, Action::MoveNE);
+        key_bindings.bind(engine::KEY_NUMPAD6, Action::MoveE);
+        key_bindings.bind(engine::KEY_NUMPAD3, Action::MoveSE);
+        key_bindings.bind(engine::KEY_NUMPAD2, Action::MoveS);
+        key_bindings.bind(engine::KEY_NUMPAD1, Action::MoveSW);
+        key_bindings.bind(engine::KEY_NUMPAD4, Action::MoveW);
+        key_bindings.bind(engine::KEY_NUMPAD7, Action::MoveNW);
+        key_bindings.bind(engine::KEY_NUMPAD5, Action::Wait);
+    }
+
+    if uses_vi_keys {
+        key_bindings.bind(engine::KEY_K, Action::MoveN);
+        key_bindings.bind(engine::KEY_U, Action::MoveNE);
+        key_bindings.bind(engine::KEY_L, Action::MoveE);
+        key_bindings.bind(engine::KEY_N, Action::MoveSE);
+        key_bindings.bind(engine::KEY_J, Action::MoveS);
+        key_bindings.bind(engine::KEY_B, Action::MoveSW);
+        key_bindings.bind(engine::KEY_H, Action::MoveW);
+        key_bindings.bind(engine::KEY_Y, Action::MoveNW);
+    }
+
+    if preset == Preset::Wasd {
+        // Keep everything within reach of the left hand.
+        key_bindings.bind(engine::KEY_W, Action::MoveN);
+        key_bindings.bind(engine::KEY_E, Action::MoveNE);
+        key_bindings.bind(engine::KEY_D, Action::MoveE);
+        key_bindings.bind(engine::KEY_C, Action::MoveSE);
+        key_bindings.bind(engine::KEY_S, Action::MoveS);
+        key_bindings.bind(engine::KEY_Z, Action::MoveSW);
+        key_bindings.bind(engine::KEY_A, Action::MoveW);
+        key_bindings.bind(engine::KEY_Q, Action::MoveNW);
+        key_bindings.bind(engine::KEY_F, Action::Wait);
+        key_bindings.bind(engine::KEY_V, Action::ToggleGuardVision);
+        key_bindings.bind(engine::KEY_G, Action::ToggleNoisePreview);
+        key_bindings.bind(engine::KEY_X, Action::Look);
+        key_bindings.bind(engine::KEY_R, Action::Explore);
+    } else {
+        key_bindings.bind(engine::KEY_PERIOD, Action::Wait);
+        key_bindings.bind(engine::KEY_V, Action::ToggleGuardVision);
+        key_bindings.bind(engine::KEY_E, Action::ToggleNoisePreview);
+        key_bindings.bind(engine::KEY_X, Action::Look);
+        key_bindings.bind(engine::KEY_O, Action::Explore);
+    }
+
+    key_bindings
+}
+
+impl KeyBindings {
+    pub fn action_for_key(&self, key: i32) -> Option<Action> {
+        self.bindings.iter().find(|(k, _)| *k == key).map(|(_, action)| *action)
+    }
+
+    pub fn keys_for_action(&self, action: Action) -> Vec<i32> {
+        self.bindings.iter().filter(|(_, a)| *a == action).map(|(key, _)| *key).collect()
+    }
+
+    // Bind a key to an action, replacing whatever the key did before.
+
+    pub fn bind(&mut self, key: i32, action: Action) {
+        self.bindings.retain(|(k, _)| *k != key);
+        self.bindings.push((key, action));
+    }
+
+    pub fn unbind_action(&mut self, action: Action) {
+        self.bindings.retain(|(_, a)| *a != action);
+    }
+
+    // Saved bindings are restored over the preset's defaults. They cover only the
+    // actions that existed when they were saved, so actions added since then keep
+    // their default keys, unless a saved binding takes the key.
+
+    pub fn unbind_saved_actions(&mut self, saved_action_count: usize) {
+        self.bindings.retain(|(_, action)| *action as usize >= saved_action_count);
+    }
+
+    // Flattened (key, action) pairs, in the form passed to engine::save_key_bindings()
+
+    pub fn to_key_action_pairs(&self) -> Vec<i32> {
+        self.bindings.iter().flat_map(|(key, action)| vec![*key, *action as i32]).collect()
+    }
+}
+
+pub fn action_from_index(index: i32) -> Option<Action> {
+    ACTIONS.iter().find(|action| **action as i32 == index).copied()
+}
+
+pub fn preset_from_index(index: i32) -> Option<Preset> {
+    PRESETS.iter().find(|preset| **preset as i32 == index).copied()
+}
+
+pub fn dir_for_action(action: Action) -> Option<Coord> {
+    match action {
+        Action::MoveN => Some(Coord(0, 1)),
+        Action::MoveNE => Some(Coord(1, 1)),
+        Action::MoveE => Some(Coord(1, 0)),
+        Action::MoveSE => Some(Coord(1, -1)),
+        Action::MoveS => Some(Coord(0, -1)),
+        Action::MoveSW => Some(Coord(-1, -1)),
+        Action::MoveW => Some(Coord(-1, 0)),
+        Action::MoveNW => Some(Coord(-1, 1)),
+        Action::Wait => Some(Coord(0, 0)),
+        _ => None,
+    }
+}
+
+pub fn name_for_key(key: i32) -> String {
+    let name = match key {
+        engine::KEY_BACKSPACE => "Backspace",
+        engine::KEY_TAB => "Tab",
+        engine::KEY_ENTER => "Enter",
+        engine::KEY_SPACE => "Space",
+        engine::KEY_PAGE_UP => "Page Up",
+        engine::KEY_PAGE_DOWN => "Page Down",
+        engine::KEY_END => "End",
+        engine::KEY_HOME => "Home",
+        engine::KEY_LEFT => "Left",
+        engine::KEY_UP => "Up",
+        engine::KEY_RIGHT => "Right",
+        engine::KEY_DOWN => "Down",
+        engine::KEY_INSERT => "Insert",
+        engine::KEY_DELETE => "Delete",
+        engine::KEY_NUMPAD_MULTIPLY => "Numpad *",
+        engine::KEY_NUMPAD_ADD => "Numpad +",
+        engine::KEY_NUMPAD_ENTER => "Numpad Enter",
+        engine::KEY_NUMPAD_SUBTRACT => "Numpad -",
+        engine::KEY_NUMPAD_DECIMAL => "Numpad .",
+        engine::KEY_NUMPAD_DIVIDE => "Numpad /",
+        engine::KEY_SEMICOLON => ";",
+        engine::KEY_EQUAL => "=",
+        engine::KEY_COMMA => ",",
+        engine::KEY_MINUS => "-",
+        engine::KEY_PERIOD => ".",
+        engine::KEY_SLASH => "/",
+        engine::KEY_BACKQUOTE => "`",
+        engine::KEY_BRACKET_LEFT => "[",
+        engine::KEY_BACKSLASH => "\\",
+        engine::KEY_BRACKET_RIGHT => "]",
+        engine::KEY_QUOTE => "'",
+        _ => "",
+    };
+
+    if !name.is_empty() {
+        String::from(name)
+    } else if (engine::KEY_0..=engine::KEY_9).contains(&key) || (engine::KEY_A..=engine::KEY_Z).contains(&key) {
+        ((key as u8) as char).to_string()
+    } else if (engine::KEY_NUMPAD0..=engine::KEY_NUMPAD9).contains(&key) {
+        format!("Numpad {}", key - engine::KEY_NUMPAD0)
+    } else {
+        format!("Key {}", key)
+    }
+}
@@ -1,19 +1,52 @@
 extern crate multiarray;
 extern crate rand;
 extern crate rand_pcg;
+#[cfg(not(target_arch = "wasm32"))]
+extern crate png;
 
-mod cell_grid;
+use std::cell::RefCell;
+
+mod animation;
+mod audio;
+pub mod cell_grid;
 mod color_preset;
-mod coord;
+pub mod coord;
+mod dialogue;
+mod font;
 mod fontdata;
 mod game;
-mod guard;
-mod random_map;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+pub mod guard;
+mod key_bindings;
+mod localization;
+mod map_layer;
+mod message_log;
+mod narration;
+pub mod random_map;
+pub mod render;
 mod speech_bubbles;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod terminal;
+mod text_layout;
+mod tiles;
+
+// Global game state; this program is single-threaded
 
-// Global game state (not thread-safe, but this program is single-threaded)
+thread_local! {
+    static GAME: RefCell<Option<game::Game>> = const { RefCell::new(None) };
+}
 
-static mut GAME: Option<game::Game> = None;
+// Sounds queued while handling an event are played once it's done.
+
+fn with_game<F>(f: F) where F: FnOnce(&mut game::Game) {
+    GAME.with(|game| {
+        if let Some(game) = game.borrow_mut().as_mut() {
+            f(game);
+            game::play_queued_sounds(game);
+        }
+    });
+}
 
 // Javascript-facing interface
 
@@ -21,28 +54,129 @@ static mut GAME: Option<game::Game> = None;
 pub fn rs_start(seed0: u32, seed1: u32) -> () {
     let seed = ((seed0 as u64) << 32) + (seed1 as u64);
     let game = game::new_game(seed);
-    unsafe { GAME = Some(game); }
+    GAME.with(|game_global| *game_global.borrow_mut() = Some(game));
+}
+
+#[no_mangle]
+pub fn rs_on_resize(screen_size_x: i32, screen_size_y: i32) {
+    with_game(|game| game::on_resize(game, screen_size_x, screen_size_y));
 }
 
 #[no_mangle]
 pub fn rs_on_draw(screen_size_x: i32, screen_size_y: i32) {
-    if let Some(game) = unsafe { &GAME } {
-        game::on_draw(&game, screen_size_x, screen_size_y);
-    }
+    let mut renderer = engine::new_wasm_renderer();
+    GAME.with(|game| {
+        if let Some(game) = game.borrow_mut().as_mut() {
+            game::on_draw(game, &mut renderer, screen_size_x, screen_size_y);
+        }
+    });
+    renderer.flush();
+}
+
+// Called every animation frame with the milliseconds since the previous one
+
+#[no_mangle]
+pub fn rs_on_tick(dt_ms: f32) {
+    with_game(|game| game::on_tick(game, dt_ms));
 }
 
 #[no_mangle]
 pub fn rs_on_key_down(key: i32, ctrl_key_down: i32, shift_key_down: i32) -> () {
-    if let Some(game) = unsafe { &mut GAME } {
-        game::on_key_down(game, key, ctrl_key_down != 0, shift_key_down != 0);
+    with_game(|game| game::on_key_down(game, key, ctrl_key_down != 0, shift_key_down != 0));
+}
+
+// Restore key bindings saved by js_save_key_bindings(): call rs_reset_key_bindings()
+// with the saved preset and action count, and then rs_bind_key() for each
+// (key, action) pair.
+
+#[no_mangle]
+pub fn rs_reset_key_bindings(preset: i32, saved_action_count: i32) {
+    with_game(|game| game::reset_key_bindings(game, preset, saved_action_count));
+}
+
+#[no_mangle]
+pub fn rs_bind_key(key: i32, action: i32) {
+    with_game(|game| game::bind_key(game, key, action));
+}
+
+// Switch the language of the game's text. Indices are: 0 English, 1 French, 2 Spanish.
+
+#[no_mangle]
+pub fn rs_set_language(language: i32) {
+    with_game(|game| game::set_language(game, language));
+}
+
+// Text is passed in from Javascript by calling rs_alloc() to get a buffer, copying
+// UTF-8 bytes into it, and handing it to a function that takes ownership of it.
+
+#[no_mangle]
+pub fn rs_alloc(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+// Replace the font with one described by the text of a BMFont .fnt file. Its page
+// image needs to be loaded as the font texture. Returns 1 on success; on failure
+// the current font stays in place and 0 is returned.
+
+/// # Safety
+///
+/// ptr must come from rs_alloc(len), with len bytes written to it.
+#[no_mangle]
+pub unsafe fn rs_load_font(ptr: *mut u8, len: usize) -> i32 {
+    let bytes = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+    let descriptor = String::from_utf8_lossy(&bytes);
+
+    match font::parse_bmfont(&descriptor) {
+        Ok(font) => {
+            font::set_font(font);
+            engine::invalidate_screen();
+            1
+        },
+        Err(_) => 0,
     }
 }
 
+// Mouse and touch positions are in drawing coordinates: pixels from the lower left
+// corner of the screen.
+
+#[no_mangle]
+pub fn rs_on_mouse_move(x: i32, y: i32, screen_size_x: i32, screen_size_y: i32) {
+    with_game(|game| game::on_mouse_move(game, x, y, screen_size_x, screen_size_y));
+}
+
+#[no_mangle]
+pub fn rs_on_mouse_leave() {
+    with_game(game::on_mouse_leave);
+}
+
+#[no_mangle]
+pub fn rs_on_mouse_down(x: i32, y: i32, screen_size_x: i32, screen_size_y: i32, button: i32, shift_key_down: i32) {
+    with_game(|game| game::on_mouse_down(game, x, y, screen_size_x, screen_size_y, button, shift_key_down != 0));
+}
+
+#[no_mangle]
+pub fn rs_on_touch_start(x: i32, y: i32, screen_size_x: i32, screen_size_y: i32) {
+    with_game(|game| game::on_touch_move(game, x, y, screen_size_x, screen_size_y));
+}
+
+#[no_mangle]
+pub fn rs_on_touch_move(x: i32, y: i32, screen_size_x: i32, screen_size_y: i32) {
+    with_game(|game| game::on_touch_move(game, x, y, screen_size_x, screen_size_y));
+}
+
+#[no_mangle]
+pub fn rs_on_touch_end(x: i32, y: i32, screen_size_x: i32, screen_size_y: i32) {
+    with_game(|game| game::on_touch_end(game, x, y, screen_size_x, screen_size_y));
+}
+
 // Rust-facing interface
 
-mod engine {
+pub mod engine {
     #![allow(dead_code)]
 
+    use crate::coord::Coord;
+    use crate::render::{Quad, Renderer};
+
     /// Key codes passed to game::on_key_down()
 
     pub const KEY_BACKSPACE: i32 = 8;
@@ -130,24 +264,88 @@ mod engine {
     pub const KEY_BRACKET_RIGHT: i32 = 221;
     pub const KEY_QUOTE: i32 = 222;
 
-    // TODO: Create an object that contains draw_rect() and draw_tile(); pass it to on_draw()
-    // to ensure that draw_rect()/draw_tile() only get called during on_draw().
+    // Mouse button numbers passed to game::on_mouse_down()
+
+    pub const MOUSE_BUTTON_LEFT: i32 = 0;
+    pub const MOUSE_BUTTON_MIDDLE: i32 = 1;
+    pub const MOUSE_BUTTON_RIGHT: i32 = 2;
+
+    // Javascript imports. Native builds, such as tests and tools, have no
+    // Javascript host, so there the imports do nothing.
+
+    #[cfg(target_arch = "wasm32")]
+    mod host {
+        use crate::render::Quad;
+
+        extern "C" {
+            pub fn js_draw_quads(quads: *const Quad, len: usize);
+            pub fn js_draw_map_layer(quads: *const Quad, len: usize, dirty: *const u32, dirty_len: usize, rebuild: i32, offset_x: i32, offset_y: i32, scale: i32);
+            pub fn js_save_key_bindings(preset: i32, action_count: i32, key_action_pairs: *const i32, len: usize);
+            pub fn js_play_sound(id: i32, volume: f32, pan: f32);
+            pub fn js_invalidate_screen();
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    mod host {
+        use crate::render::Quad;
+
+        pub unsafe fn js_draw_quads(_quads: *const Quad, _len: usize) {}
+        #[allow(clippy::too_many_arguments)] // mirrors the JS import, which only takes scalars
+        pub unsafe fn js_draw_map_layer(_quads: *const Quad, _len: usize, _dirty: *const u32, _dirty_len: usize, _rebuild: i32, _offset_x: i32, _offset_y: i32, _scale: i32) {}
+        pub unsafe fn js_save_key_bindings(_preset: i32, _action_count: i32, _key_action_pairs: *const i32, _len: usize) {}
+        pub unsafe fn js_play_sound(_id: i32, _volume: f32, _pan: f32) {}
+        pub unsafe fn js_invalidate_screen() {}
+    }
+
+    // Drawing is batched: the game draws quads into a WasmRenderer, and
+    // Javascript reads them all from wasm memory in one call when it's flushed,
+    // at the end of the frame or before a map layer is drawn.
+
+    pub struct WasmRenderer {
+        quads: Vec<Quad>,
+    }
+
+    pub fn new_wasm_renderer() -> WasmRenderer {
+        WasmRenderer { quads: Vec::new() }
+    }
+
+    impl WasmRenderer {
+        /// Send the buffered quads to be drawn
+        pub fn flush(&mut self) {
+            if !self.quads.is_empty() {
+                unsafe { host::js_draw_quads(self.quads.as_ptr(), self.quads.len()); }
+                self.quads.clear();
+            }
+        }
+    }
+
+    impl Renderer for WasmRenderer {
+        fn draw_quad(&mut self, quad: Quad) {
+            self.quads.push(quad);
+        }
+
+        /// Javascript keeps the layer in vertex buffers between frames.
+        fn draw_map_layer(&mut self, quads: &[Quad], dirty: &[u32], rebuild: bool, offset: Coord, scale: i32) {
+            self.flush();
+            unsafe { host::js_draw_map_layer(quads.as_ptr(), quads.len(), dirty.as_ptr(), dirty.len(), rebuild as i32, offset.0, offset.1, scale); }
+        }
+    }
 
-    /// Fill a rectangle with a solid color. Only call during game::on_draw().
-    pub fn draw_rect(dest_x: i32, dest_y: i32, size_x: i32, size_y: i32, color: u32) {
-        extern { fn js_draw_rect(dest_x: i32, dest_y: i32, size_x: i32, size_y: i32, color: u32); }
-        unsafe { js_draw_rect(dest_x, dest_y, size_x, size_y, color) };
+    /// Store key bindings between sessions: the preset they started from, how many
+    /// actions there are, and a flat list of (key code, action) pairs
+    pub fn save_key_bindings(preset: i32, action_count: i32, key_action_pairs: &[i32]) {
+        unsafe { host::js_save_key_bindings(preset, action_count, key_action_pairs.as_ptr(), key_action_pairs.len()); }
     }
 
-    /// Copy a rectangular area from a texture to the screen, multiplied by a color. Only call during game::on_draw().
-    pub fn draw_tile(dest_x: i32, dest_y: i32, size_x: i32, size_y: i32, color: u32, texture_index: u32, src_x: i32, src_y: i32) {
-        extern { fn js_draw_tile(dest_x: i32, dest_y: i32, size_x: i32, size_y: i32, color: u32, texture_index: u32, src_x: i32, src_y: i32); }
-        unsafe { js_draw_tile(dest_x, dest_y, size_x, size_y, color, texture_index, src_x, src_y); }
+    /// Play a sound effect; id is an audio::Sound. Volume ranges from 0 to 1, and pan
+    /// from -1 (left) to 1 (right).
+    pub fn play_sound(id: i32, volume: f32, pan: f32) {
+        unsafe { host::js_play_sound(id, volume, pan); }
     }
 
     /// Request game::on_draw() to be called
     pub fn invalidate_screen() {
-        extern { fn js_invalidate_screen(); }
-        unsafe { js_invalidate_screen(); }
+        unsafe { host::js_invalidate_screen(); }
     }
 }
@@ -0,0 +1,459 @@
+use crate::cell_grid::{CellType, ItemKind};
+use crate::dialogue::{ANY, Disguise, Exchange, Line, exchange, line};
+use crate::guard::{GuardKind, GuardMode};
+use crate::key_bindings::{Action, Preset};
+use crate::narration::NarrationEvent;
+
+use super::{LineSet, Msg};
+
+// English is the fallback for every other language, so it must cover every message.
+
+pub fn text(msg: Msg) -> &'static str {
+    match msg {
+        Msg::CellType(cell_type) => name_for_cell_type(cell_type),
+        Msg::ItemKind(kind) => name_for_item_kind(kind),
+        Msg::GuardKind(GuardKind::Outer) => "Outer guard",
+        Msg::GuardKind(GuardKind::Inner) => "Inner guard",
+        Msg::GuardMode(mode) => description_for_guard_mode(mode),
+        Msg::Action(action) => name_for_action(action),
+        Msg::Preset(preset) => name_for_preset(preset),
+        Msg::Narration(event) => text_for_narration_event(event),
+        Msg::North => "north",
+        Msg::Northeast => "northeast",
+        Msg::East => "east",
+        Msg::Southeast => "southeast",
+        Msg::South => "south",
+        Msg::Southwest => "southwest",
+        Msg::West => "west",
+        Msg::Northwest => "northwest",
+
+        Msg::Creak => "\u{ab}creak\u{bb}",
+
+        Msg::Unexplored => "Unexplored",
+        Msg::CellLit => "{0} (lit)",
+        Msg::CellDark => "{0} (dark)",
+        Msg::CanHideHere => "You can hide here",
+        Msg::You => "You",
+        Msg::GuardFacing => "{0}, facing {1}",
+        Msg::VisibleToGuard => "Visible to the {0} to the {1}",
+
+        Msg::Someone => "Someone",
+        Msg::Here => "here",
+        Msg::DistanceAndDirection => "{0} {1}",
+        Msg::LogLevelHeading => "Level {0}",
+        Msg::LogNarration => "Turn {0}: {1}",
+        Msg::LogSpeech => "Turn {0}: {1} ({2}): {3}",
+        Msg::LogEmpty => "Nothing has been said or heard yet.",
+        Msg::More => "(more)",
+
+        Msg::KeyBindingsTitle => "Key Bindings",
+        Msg::PresetLabel => "Preset",
+        Msg::PressKeyToBind => "Press a key to add (Esc to cancel)",
+        Msg::NoKeys => "(none)",
+
+        Msg::Health => "Health",
+        Msg::Air => "Air",
+        Msg::LevelSeen => "Level {0}: {1}% Seen",
+        Msg::LootUnknownTotal => "Loot {0}/?",
+        Msg::Loot => "Loot {0}/{1}",
+        Msg::HelpPageNumber => "Page {0} of {1}",
+        Msg::HelpBarHint => "Press left/right arrow keys (or tap either side) to view help, or Esc to close",
+        Msg::MessageLogBarHint => "Message log: up/down or Page Up/Page Down to scroll; press Esc to close",
+        Msg::OverviewBarHint => "Map overview: click a known cell to look at it; press Esc to close",
+        Msg::KeyBindingsBarHint => "Up/down to choose, Enter to change or add a key, Delete to clear, Esc to close",
+        Msg::LookBarHint => "Look mode: move the cursor with the movement keys. Enter travels there; {0} or Esc stops looking.",
+        Msg::PressForHelp => "Press {0} for help",
+
+        Msg::HelpPage(0) => HELP_PAGE_INTRO,
+        Msg::HelpPage(1) => HELP_PAGE_HINTS,
+        Msg::HelpPage(_) => HELP_PAGE_TOOLS,
+    }
+}
+
+fn name_for_cell_type(cell_type: CellType) -> &'static str {
+    match cell_type {
+        CellType::GroundNormal     => "Ground",
+        CellType::GroundGrass      => "Grass",
+        CellType::GroundWater      => "Water",
+        CellType::GroundMarble     => "Marble floor",
+        CellType::GroundWood       => "Wood floor",
+        CellType::GroundWoodCreaky => "Creaky wood floor",
+        CellType::Wall0000 |
+        CellType::Wall0001 |
+        CellType::Wall0010 |
+        CellType::Wall0011 |
+        CellType::Wall0100 |
+        CellType::Wall0101 |
+        CellType::Wall0110 |
+        CellType::Wall0111 |
+        CellType::Wall1000 |
+        CellType::Wall1001 |
+        CellType::Wall1010 |
+        CellType::Wall1011 |
+        CellType::Wall1100 |
+        CellType::Wall1101 |
+        CellType::Wall1110 |
+        CellType::Wall1111         => "Wall",
+        CellType::OneWayWindowE    => "One-way window (exit east)",
+        CellType::OneWayWindowW    => "One-way window (exit west)",
+        CellType::OneWayWindowN    => "One-way window (exit north)",
+        CellType::OneWayWindowS    => "One-way window (exit south)",
+        CellType::PortcullisNS     => "Portcullis",
+        CellType::PortcullisEW     => "Portcullis",
+        CellType::DoorNS           => "Doorway",
+        CellType::DoorEW           => "Doorway",
+    }
+}
+
+fn name_for_item_kind(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Chair => "Chair",
+        ItemKind::Table => "Table",
+        ItemKind::Bush => "Bush",
+        ItemKind::Coin => "Gold coin",
+        ItemKind::DoorNS => "Door",
+        ItemKind::DoorEW => "Door",
+        ItemKind::PortcullisNS => "Portcullis",
+        ItemKind::PortcullisEW => "Portcullis",
+        ItemKind::Outfit(None) => "Your own clothes",
+        ItemKind::Outfit(Some(GuardKind::Outer)) => "Outer guard's uniform",
+        ItemKind::Outfit(Some(GuardKind::Inner)) => "Inner guard's uniform",
+    }
+}
+
+fn description_for_guard_mode(mode: GuardMode) -> &'static str {
+    match mode {
+        GuardMode::Patrol => "Patrolling",
+        GuardMode::Look => "Looking at something",
+        GuardMode::LookAtDisguised => "Looking at a stranger",
+        GuardMode::Listen => "Listening",
+        GuardMode::ChaseVisibleTarget => "Chasing the thief",
+        GuardMode::MoveToLastSighting => "Searching where the thief was seen",
+        GuardMode::MoveToLastSound => "Investigating a noise",
+        GuardMode::MoveToGuardShout => "Answering a shout",
+        GuardMode::SearchForMissingLoot => "Searching for stolen loot",
+    }
+}
+
+fn name_for_action(action: Action) -> &'static str {
+    match action {
+        Action::MoveN => "Move north",
+        Action::MoveNE => "Move northeast",
+        Action::MoveE => "Move east",
+        Action::MoveSE => "Move southeast",
+        Action::MoveS => "Move south",
+        Action::MoveSW => "Move southwest",
+        Action::MoveW => "Move west",
+        Action::MoveNW => "Move northwest",
+        Action::Wait => "Wait",
+        Action::Help => "Help",
+        Action::ToggleMessages => "Show/hide speech",
+        Action::ToggleGuardVision => "Show/hide guard vision",
+        Action::ToggleNoisePreview => "Show/hide noise range",
+        Action::Look => "Look around",
+        Action::Explore => "Explore",
+        Action::ZoomIn => "Zoom in",
+        Action::ZoomOut => "Zoom out",
+        Action::ZoomToFit => "Zoom to fit",
+        Action::ToggleMinimap => "Show/hide minimap",
+        Action::Overview => "Map overview",
+        Action::MessageLog => "Message log",
+    }
+}
+
+fn name_for_preset(preset: Preset) -> &'static str {
+    match preset {
+        Preset::Standard => "Standard (numpad, vi keys)",
+        Preset::ViKeys => "Vi keys",
+        Preset::Numpad => "Numpad",
+        Preset::Wasd => "WASD + QEZC",
+    }
+}
+
+fn text_for_narration_event(event: NarrationEvent) -> &'static str {
+    match event {
+        NarrationEvent::LevelStart(0) => "Collect the gold coins and reveal the whole mansion.",
+        NarrationEvent::LevelStart(1) => "Watch out for the patrolling guard!",
+        NarrationEvent::LevelStart(_) => "Another mansion. Take the gold and map every room.",
+        NarrationEvent::LastCoinTaken => "That's the last of the gold. Now finish mapping the mansion.",
+        NarrationEvent::AllSeen => "You've seen the whole mansion. Now find the rest of the gold.",
+        NarrationEvent::ExitReachable => "Mansion cleared! Move off the edge of the map to escape.",
+        NarrationEvent::AlarmRaised => "You've been spotted!",
+        NarrationEvent::DisguiseBlown => "Your disguise has been seen through!",
+        NarrationEvent::PlayerDied => "You are dead! Press Ctrl+R or tap the map for a new game.",
+    }
+}
+
+static HELP_PAGE_INTRO: &str =
+"Disguiser
+
+Press right arrow for hints, or {0} to toggle this help
+
+Sneak into mansions, map them, steal all the loot and get out.
+
+The guards cannot be injured! They also cannot cut corners diagonally.
+
+Move north, northeast, east, southeast, south, southwest, west and northwest with {1}, and press {2} to wait. Arrow keys with Shift/Ctrl plus Left/Right also move diagonally. Press Esc to change the keys.
+
+Health is shown on the status bar in the lower left.
+
+A 2021 Seven-day Roguelike Challenge game by James McNeill
+
+Special Thanks: Mendi Carroll, Mike Gaffney
+
+mcneja.github.io
+playtechs.blogspot.com";
+
+static HELP_PAGE_HINTS: &str =
+"Hints
+
+Pick up gold coins by moving over them.
+
+Diagonal movement is critical! Guards cannot cut corners, so moving diagonally around corners is the key to gaining distance from them.
+
+Guards can only see ahead of themselves.
+
+If a guard sees you and is standing next to you, he will attack!
+
+Bushes, tables, and water can all serve as hiding places. Patrolling guards cannot see you when you are hidden. Alert guards (with a question mark over their heads) can see you if they are next to you.
+
+High one-way windows allow for quick escapes. Guards can't use them!
+
+Guards can't see as far in the dark outside the mansion.";
+
+static HELP_PAGE_TOOLS: &str =
+"Tools
+
+Press {3} to show what the guards can see: yellow where lit, blue where dark.
+
+Creaky floors make noise. Press {4} to show how far a noise would carry from where you stand, and which guards would hear it.
+
+Press {5} to examine the map. Move the cursor with the movement keys, and press {5} or Esc when done, or Enter to travel to the cursor.
+
+Press {6} and {7} to zoom, or {8} to fit the view to the screen. Press {9} to show or hide the minimap, and {10} for an overview of the whole map. Press {11} to look back over what's been said and heard.
+
+Press {12} to explore automatically. Travel and exploring stop as soon as a guard comes into view or something makes a noise.
+
+With a mouse or touch screen, click a cell to step or travel there, and right-click (or Shift+click) it to examine it. Tap the top bar for help.";
+
+pub fn lines(line_set: LineSet) -> &'static [Line] {
+    match line_set {
+        LineSet::See => SEE_LINES,
+        LineSet::SeeDisguised => SEE_DISGUISED_LINES,
+        LineSet::Hear => HEAR_LINES,
+        LineSet::HearGuard => HEAR_GUARD_LINES,
+        LineSet::Chase => CHASE_LINES,
+        LineSet::Investigate => INVESTIGATE_LINES,
+        LineSet::EndChase => END_CHASE_LINES,
+        LineSet::EndInvestigation => END_INVESTIGATION_LINES,
+        LineSet::DoneLooking => DONE_LOOKING_LINES,
+        LineSet::DoneSeeingDisguised => DONE_SEEING_DISGUISED_LINES,
+        LineSet::DoneListening => DONE_LISTENING_LINES,
+        LineSet::Damage => DAMAGE_LINES,
+        LineSet::NoticeMissingLoot => NOTICE_MISSING_LOOT_LINES,
+        LineSet::EndSearch => END_SEARCH_LINES,
+    }
+}
+
+pub fn exchanges() -> &'static [Exchange] {
+    EXCHANGES
+}
+
+static SEE_LINES: &[Line] = &[
+    line("Who goes there?"),
+    line("Huh?"),
+    line("What?"),
+    line("Wait..."),
+    line("Who's that?"),
+    line("Hey..."),
+    line("Hmm..."),
+    line("What moved?"),
+    line("Did that shadow move?"),
+    line("I see something..."),
+    line("Hello?"),
+    line("Not again...").when(ANY.wary(true)).weight(2),
+    line("Show yourself!").when(ANY.wary(true)).weight(2),
+    line("There it is again!").when(ANY.alert(true)).weight(2),
+    line("Did you see that?").when(ANY.near_guards(true)).weight(2),
+];
+
+static SEE_DISGUISED_LINES: &[Line] = &[
+    line("Who are you?"),
+    line("You don't look familiar!"),
+    line("Do I know you?"),
+    line("Wait..."),
+    line("Hey..."),
+    line("Let me see your face..."),
+    line("Do you belong here?"),
+    line("You are...?"),
+    line("Are you new here?"),
+    line("Which gate are you on?").when(ANY.guard_kind(GuardKind::Outer).disguise(Disguise::AsSameKind)).weight(3),
+    line("Since when do you work inside?").when(ANY.guard_kind(GuardKind::Inner).disguise(Disguise::AsSameKind)).weight(3),
+];
+
+static HEAR_LINES: &[Line] = &[
+    line("Huh?"),
+    line("What?"),
+    line("Hark!"),
+    line("A noise..."),
+    line("I heard something."),
+    line("Hmm..."),
+    line("Who goes there?"),
+    line("What's that noise?"),
+    line("I hear something..."),
+    line("Hello?"),
+    line("Did you hear that?").when(ANY.near_guards(true)).weight(2),
+    line("That's no rat...").when(ANY.wary(true)).weight(2),
+    line("There, again!").when(ANY.alert(true)).weight(2),
+];
+
+static HEAR_GUARD_LINES: &[Line] = &[
+    line("Where?"),
+    line("I'm coming!"),
+    line("Here I come!"),
+    line("To arms!"),
+    line("Where is he?"),
+];
+
+static CHASE_LINES: &[Line] = &[
+    line("Halt!"),
+    line("Hey!"),
+    line("Aha!"),
+    line("I see you!"),
+    line("I'm coming!"),
+    line("I'll get you!"),
+    line("Just you wait..."),
+    line("You won't get away!"),
+    line("Oh no you don't..."),
+    line("Get him!"),
+    line("After him!"),
+    line("Thief!"),
+    line("You again!").when(ANY.wary(true)).weight(2),
+    line("Help me here!").when(ANY.near_guards(true)).weight(2),
+    line("Intruder!").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static INVESTIGATE_LINES: &[Line] = &[
+    line("That noise again..."),
+    line("I heard it again!"),
+    line("Someone's there!"),
+    line("Who could that be?"),
+    line("There it is again!"),
+    line("What was that?"),
+    line("Better check it out..."),
+    line("What keeps making those noises?"),
+    line("That better be rats!"),
+    line("Again?"),
+    line("Not this time...").when(ANY.wary(true)).weight(2),
+];
+
+static END_CHASE_LINES: &[Line] = &[
+    line("(huff, huff)"),
+    line("Where did he go?"),
+    line("Lost him!"),
+    line("Gone!"),
+    line("Come back!"),
+    line("Argh!"),
+    line("He's not coming back."),
+    line("Blast!"),
+    line("Next time!"),
+    line("Slippery one!").when(ANY.wary(true)).weight(2),
+];
+
+static END_INVESTIGATION_LINES: &[Line] = &[
+    line("Guess it was nothing."),
+    line("Wonder what it was?"),
+    line("Better get back."),
+    line("It's quiet now."),
+    line("This is where I heard it..."),
+    line("Nothing, now."),
+];
+
+static DONE_LOOKING_LINES: &[Line] = &[
+    line("Must have been rats."),
+    line("Too much coffee!"),
+    line("I've got the jitters."),
+    line("Probably nothing."),
+    line("I thought I saw something."),
+    line("Oh well."),
+    line("Nothing."),
+    line("Can't see it now."),
+    line("I've been up too long.").when(ANY.after_turn(300)),
+    line("Seeing things, I guess."),
+    line("Hope it wasn't anything."),
+    line("Did I imagine that?"),
+    line("My shift can't end soon enough.").when(ANY.after_turn(300)).weight(2),
+    line("He's still out there somewhere.").when(ANY.wary(true)).weight(2),
+    line("Where did it go?").when(ANY.alert(true)).weight(2),
+];
+
+static DONE_SEEING_DISGUISED_LINES: &[Line] = &[
+    line("Who was that?"),
+    line("Huh..."),
+    line("I wonder who that was?"),
+    line("Oh well."),
+    line("I'm seeing things."),
+    line("I've been up too long.").when(ANY.after_turn(300)),
+    line("Seeing things, I guess."),
+    line("Probably new here."),
+    line("Better get back to it."),
+    line("Did I imagine that?"),
+    line("Should I tell the boss?"),
+];
+
+static DONE_LISTENING_LINES: &[Line] = &[
+    line("Must have been rats."),
+    line("Too much coffee!"),
+    line("I've got the jitters."),
+    line("Probably nothing."),
+    line("I thought I heard something."),
+    line("Oh well."),
+    line("Nothing."),
+    line("Can't hear it now."),
+    line("I've been up too long.").when(ANY.after_turn(300)),
+    line("Hearing things, I guess."),
+    line("Hope it wasn't anything."),
+    line("Did I imagine that?"),
+    line("I know you're out there...").when(ANY.wary(true)).weight(2),
+];
+
+static NOTICE_MISSING_LOOT_LINES: &[Line] = &[
+    line("Where's the gold?"),
+    line("We've been robbed!"),
+    line("The coins are gone!"),
+    line("Someone's been stealing!"),
+    line("Wasn't there gold here?"),
+    line("Thief in the house!"),
+    line("Who took the loot?"),
+    line("The boss will have my head!"),
+    line("The master's gold!").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static END_SEARCH_LINES: &[Line] = &[
+    line("No sign of anyone."),
+    line("Whoever it was is gone."),
+    line("The boss won't like this."),
+    line("I'll keep my eyes open."),
+    line("Gone without a trace."),
+    line("Better report this later."),
+];
+
+static DAMAGE_LINES: &[Line] = &[
+    line("Oof!"),
+    line("Krak!"),
+    line("Pow!"),
+    line("Urk!"),
+    line("Smack!"),
+    line("Bif!"),
+];
+
+static EXCHANGES: &[Exchange] = &[
+    exchange(&["Quiet night.", "Too quiet."]),
+    exchange(&["Anything to report?", "Nothing yet.", "Keep your eyes open."]),
+    exchange(&["Seen anything?", "Just rats.", "This place is full of them."]),
+    exchange(&["Cold out here.", "Better than inside with the boss."]).when(ANY.guard_kind(GuardKind::Outer)),
+    exchange(&["Who's watching the gold?", "You are, I thought.", "...Right."]).when(ANY.guard_kind(GuardKind::Inner)),
+    exchange(&["Any sign of the thief?", "Not yet.", "He's here somewhere.", "We'll find him."]).when(ANY.wary(true)).weight(3),
+    exchange(&["When does our shift end?", "Not soon enough."]).when(ANY.after_turn(300)).weight(2),
+    exchange(&["Who was that new fellow?", "What new fellow?", "Never mind."]).when(ANY.disguise(Disguise::AsSameKind)),
+];
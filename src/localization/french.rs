@@ -0,0 +1,461 @@
+use crate::cell_grid::{CellType, ItemKind};
+use crate::dialogue::{ANY, Disguise, Exchange, Line, exchange, line};
+use crate::guard::{GuardKind, GuardMode};
+use crate::key_bindings::{Action, Preset};
+use crate::narration::NarrationEvent;
+
+use super::{LineSet, Msg};
+
+pub fn text(msg: Msg) -> Option<&'static str> {
+    let s = match msg {
+        Msg::CellType(cell_type) => name_for_cell_type(cell_type),
+        Msg::ItemKind(kind) => name_for_item_kind(kind),
+        Msg::GuardKind(GuardKind::Outer) => "Garde extérieur",
+        Msg::GuardKind(GuardKind::Inner) => "Garde intérieur",
+        Msg::GuardMode(mode) => description_for_guard_mode(mode),
+        Msg::Action(action) => name_for_action(action),
+        Msg::Preset(preset) => name_for_preset(preset),
+        Msg::Narration(event) => text_for_narration_event(event),
+        Msg::North => "nord",
+        Msg::Northeast => "nord-est",
+        Msg::East => "est",
+        Msg::Southeast => "sud-est",
+        Msg::South => "sud",
+        Msg::Southwest => "sud-ouest",
+        Msg::West => "ouest",
+        Msg::Northwest => "nord-ouest",
+
+        Msg::Creak => "\u{ab}crac\u{bb}",
+
+        Msg::Unexplored => "Inexploré",
+        Msg::CellLit => "{0} (à la lumière)",
+        Msg::CellDark => "{0} (dans l'ombre)",
+        Msg::CanHideHere => "Vous pouvez vous cacher ici",
+        Msg::You => "Vous",
+        Msg::GuardFacing => "{0}, orienté {1}",
+        Msg::VisibleToGuard => "Visible par le {0} ({1})",
+
+        Msg::Someone => "Quelqu'un",
+        Msg::Here => "ici",
+        Msg::DistanceAndDirection => "{0} {1}",
+        Msg::LogLevelHeading => "Niveau {0}",
+        Msg::LogNarration => "Tour {0} : {1}",
+        Msg::LogSpeech => "Tour {0} : {1} ({2}) : {3}",
+        Msg::LogEmpty => "Rien n'a encore été dit ni entendu.",
+        Msg::More => "(suite)",
+
+        Msg::KeyBindingsTitle => "Configuration des touches",
+        Msg::PresetLabel => "Préréglage",
+        Msg::PressKeyToBind => "Appuyez sur une touche à ajouter (Échap pour annuler)",
+        Msg::NoKeys => "(aucune)",
+
+        Msg::Health => "Santé",
+        Msg::Air => "Air",
+        Msg::LevelSeen => "Niveau {0} : {1} % vu",
+        Msg::LootUnknownTotal => "Butin {0}/?",
+        Msg::Loot => "Butin {0}/{1}",
+        Msg::HelpPageNumber => "Page {0} sur {1}",
+        Msg::HelpBarHint => "Flèches gauche/droite (ou touchez un côté) pour parcourir l'aide, Échap pour fermer",
+        Msg::MessageLogBarHint => "Journal : haut/bas ou Page préc./Page suiv. pour faire défiler ; Échap pour fermer",
+        Msg::OverviewBarHint => "Vue d'ensemble : cliquez sur une case connue pour l'examiner ; Échap pour fermer",
+        Msg::KeyBindingsBarHint => "Haut/bas pour choisir, Entrée pour changer ou ajouter une touche, Suppr pour effacer, Échap pour fermer",
+        Msg::LookBarHint => "Examen : déplacez le curseur avec les touches de déplacement. Entrée pour y aller ; {0} ou Échap pour arrêter.",
+        Msg::PressForHelp => "Appuyez sur {0} pour l'aide",
+
+        Msg::HelpPage(0) => HELP_PAGE_INTRO,
+        Msg::HelpPage(1) => HELP_PAGE_HINTS,
+        Msg::HelpPage(_) => HELP_PAGE_TOOLS,
+    };
+
+    Some(s)
+}
+
+fn name_for_cell_type(cell_type: CellType) -> &'static str {
+    match cell_type {
+        CellType::GroundNormal     => "Sol",
+        CellType::GroundGrass      => "Herbe",
+        CellType::GroundWater      => "Eau",
+        CellType::GroundMarble     => "Sol en marbre",
+        CellType::GroundWood       => "Parquet",
+        CellType::GroundWoodCreaky => "Parquet qui grince",
+        CellType::Wall0000 |
+        CellType::Wall0001 |
+        CellType::Wall0010 |
+        CellType::Wall0011 |
+        CellType::Wall0100 |
+        CellType::Wall0101 |
+        CellType::Wall0110 |
+        CellType::Wall0111 |
+        CellType::Wall1000 |
+        CellType::Wall1001 |
+        CellType::Wall1010 |
+        CellType::Wall1011 |
+        CellType::Wall1100 |
+        CellType::Wall1101 |
+        CellType::Wall1110 |
+        CellType::Wall1111         => "Mur",
+        CellType::OneWayWindowE    => "Fenêtre à sens unique (sortie à l'est)",
+        CellType::OneWayWindowW    => "Fenêtre à sens unique (sortie à l'ouest)",
+        CellType::OneWayWindowN    => "Fenêtre à sens unique (sortie au nord)",
+        CellType::OneWayWindowS    => "Fenêtre à sens unique (sortie au sud)",
+        CellType::PortcullisNS     => "Herse",
+        CellType::PortcullisEW     => "Herse",
+        CellType::DoorNS           => "Embrasure de porte",
+        CellType::DoorEW           => "Embrasure de porte",
+    }
+}
+
+fn name_for_item_kind(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Chair => "Chaise",
+        ItemKind::Table => "Table",
+        ItemKind::Bush => "Buisson",
+        ItemKind::Coin => "Pièce d'or",
+        ItemKind::DoorNS => "Porte",
+        ItemKind::DoorEW => "Porte",
+        ItemKind::PortcullisNS => "Herse",
+        ItemKind::PortcullisEW => "Herse",
+        ItemKind::Outfit(None) => "Vos propres vêtements",
+        ItemKind::Outfit(Some(GuardKind::Outer)) => "Uniforme de garde extérieur",
+        ItemKind::Outfit(Some(GuardKind::Inner)) => "Uniforme de garde intérieur",
+    }
+}
+
+fn description_for_guard_mode(mode: GuardMode) -> &'static str {
+    match mode {
+        GuardMode::Patrol => "En patrouille",
+        GuardMode::Look => "Regarde quelque chose",
+        GuardMode::LookAtDisguised => "Dévisage un inconnu",
+        GuardMode::Listen => "Écoute",
+        GuardMode::ChaseVisibleTarget => "Poursuit le voleur",
+        GuardMode::MoveToLastSighting => "Fouille là où le voleur a été vu",
+        GuardMode::MoveToLastSound => "Enquête sur un bruit",
+        GuardMode::MoveToGuardShout => "Répond à un appel",
+        GuardMode::SearchForMissingLoot => "Cherche le butin volé",
+    }
+}
+
+fn name_for_action(action: Action) -> &'static str {
+    match action {
+        Action::MoveN => "Aller au nord",
+        Action::MoveNE => "Aller au nord-est",
+        Action::MoveE => "Aller à l'est",
+        Action::MoveSE => "Aller au sud-est",
+        Action::MoveS => "Aller au sud",
+        Action::MoveSW => "Aller au sud-ouest",
+        Action::MoveW => "Aller à l'ouest",
+        Action::MoveNW => "Aller au nord-ouest",
+        Action::Wait => "Attendre",
+        Action::Help => "Aide",
+        Action::ToggleMessages => "Afficher/masquer les paroles",
+        Action::ToggleGuardVision => "Afficher/masquer la vue des gardes",
+        Action::ToggleNoisePreview => "Afficher/masquer la portée du bruit",
+        Action::Look => "Examiner",
+        Action::Explore => "Explorer",
+        Action::ZoomIn => "Zoom avant",
+        Action::ZoomOut => "Zoom arrière",
+        Action::ZoomToFit => "Ajuster à l'écran",
+        Action::ToggleMinimap => "Afficher/masquer la minicarte",
+        Action::Overview => "Vue d'ensemble",
+        Action::MessageLog => "Journal des messages",
+    }
+}
+
+fn name_for_preset(preset: Preset) -> &'static str {
+    match preset {
+        Preset::Standard => "Standard (pavé numérique, touches vi)",
+        Preset::ViKeys => "Touches vi",
+        Preset::Numpad => "Pavé numérique",
+        Preset::Wasd => "WASD + QEZC",
+    }
+}
+
+fn text_for_narration_event(event: NarrationEvent) -> &'static str {
+    match event {
+        NarrationEvent::LevelStart(0) => "Ramassez les pièces d'or et explorez tout le manoir.",
+        NarrationEvent::LevelStart(1) => "Attention au garde qui patrouille !",
+        NarrationEvent::LevelStart(_) => "Un autre manoir. Prenez l'or et explorez chaque pièce.",
+        NarrationEvent::LastCoinTaken => "C'était le dernier sou. Finissez maintenant d'explorer le manoir.",
+        NarrationEvent::AllSeen => "Vous avez vu tout le manoir. Trouvez maintenant le reste de l'or.",
+        NarrationEvent::ExitReachable => "Manoir dévalisé ! Sortez par un bord de la carte pour vous échapper.",
+        NarrationEvent::AlarmRaised => "Vous avez été repéré !",
+        NarrationEvent::DisguiseBlown => "Votre déguisement a été percé à jour !",
+        NarrationEvent::PlayerDied => "Vous êtes mort ! Appuyez sur Ctrl+R ou touchez la carte pour une nouvelle partie.",
+    }
+}
+
+static HELP_PAGE_INTRO: &str =
+"Disguiser
+
+Flèche droite pour des conseils, ou {0} pour afficher/masquer cette aide
+
+Infiltrez des manoirs, explorez-les, volez tout le butin et ressortez.
+
+Les gardes ne peuvent pas être blessés ! Ils ne peuvent pas non plus couper les coins en diagonale.
+
+Déplacez-vous vers le nord, le nord-est, l'est, le sud-est, le sud, le sud-ouest, l'ouest et le nord-ouest avec {1}, et appuyez sur {2} pour attendre. Les flèches avec Maj/Ctrl plus Gauche/Droite permettent aussi les diagonales. Appuyez sur Échap pour changer les touches.
+
+La santé est affichée en bas à gauche, dans la barre d'état.
+
+Un jeu de James McNeill pour le Seven-day Roguelike Challenge 2021
+
+Remerciements : Mendi Carroll, Mike Gaffney
+
+mcneja.github.io
+playtechs.blogspot.com";
+
+static HELP_PAGE_HINTS: &str =
+"Conseils
+
+Ramassez les pièces d'or en passant dessus.
+
+Les déplacements en diagonale sont essentiels ! Les gardes ne peuvent pas couper les coins : contourner les angles en diagonale est le meilleur moyen de les distancer.
+
+Les gardes ne voient que devant eux.
+
+Si un garde vous voit et se tient à côté de vous, il attaque !
+
+Les buissons, les tables et l'eau peuvent servir de cachettes. Les gardes en patrouille ne vous voient pas quand vous êtes caché. Les gardes en alerte (avec un point d'interrogation au-dessus de la tête) vous voient s'ils sont à côté de vous.
+
+Les hautes fenêtres à sens unique permettent de fuir rapidement. Les gardes ne peuvent pas les utiliser !
+
+Dans l'obscurité, hors du manoir, les gardes voient moins loin.";
+
+static HELP_PAGE_TOOLS: &str =
+"Outils
+
+Appuyez sur {3} pour montrer ce que voient les gardes : en jaune là où c'est éclairé, en bleu dans l'ombre.
+
+Les parquets qui grincent font du bruit. Appuyez sur {4} pour montrer jusqu'où porterait un bruit fait là où vous êtes, et quels gardes l'entendraient.
+
+Appuyez sur {5} pour examiner la carte. Déplacez le curseur avec les touches de déplacement, puis appuyez sur {5} ou Échap pour terminer, ou sur Entrée pour vous rendre au curseur.
+
+Appuyez sur {6} et {7} pour zoomer, ou sur {8} pour ajuster la vue à l'écran. {9} affiche ou masque la minicarte, et {10} donne une vue d'ensemble de la carte. {11} permet de relire ce qui a été dit et entendu.
+
+Appuyez sur {12} pour explorer automatiquement. Les déplacements et l'exploration s'arrêtent dès qu'un garde apparaît ou qu'un bruit se fait entendre.
+
+Avec une souris ou un écran tactile, cliquez sur une case pour vous y rendre, et faites un clic droit (ou Maj+clic) pour l'examiner. Touchez la barre du haut pour l'aide.";
+
+pub fn lines(line_set: LineSet) -> Option<&'static [Line]> {
+    let lines = match line_set {
+        LineSet::See => SEE_LINES,
+        LineSet::SeeDisguised => SEE_DISGUISED_LINES,
+        LineSet::Hear => HEAR_LINES,
+        LineSet::HearGuard => HEAR_GUARD_LINES,
+        LineSet::Chase => CHASE_LINES,
+        LineSet::Investigate => INVESTIGATE_LINES,
+        LineSet::EndChase => END_CHASE_LINES,
+        LineSet::EndInvestigation => END_INVESTIGATION_LINES,
+        LineSet::DoneLooking => DONE_LOOKING_LINES,
+        LineSet::DoneSeeingDisguised => DONE_SEEING_DISGUISED_LINES,
+        LineSet::DoneListening => DONE_LISTENING_LINES,
+        LineSet::Damage => DAMAGE_LINES,
+        LineSet::NoticeMissingLoot => NOTICE_MISSING_LOOT_LINES,
+        LineSet::EndSearch => END_SEARCH_LINES,
+    };
+
+    Some(lines)
+}
+
+pub fn exchanges() -> Option<&'static [Exchange]> {
+    Some(EXCHANGES)
+}
+
+static SEE_LINES: &[Line] = &[
+    line("Qui va là ?"),
+    line("Hein ?"),
+    line("Quoi ?"),
+    line("Attends..."),
+    line("Qui est là ?"),
+    line("Hé..."),
+    line("Hmm..."),
+    line("Qu'est-ce qui a bougé ?"),
+    line("Cette ombre a bougé ?"),
+    line("Je vois quelque chose..."),
+    line("Il y a quelqu'un ?"),
+    line("Encore ça...").when(ANY.wary(true)).weight(2),
+    line("Montre-toi !").when(ANY.wary(true)).weight(2),
+    line("Encore là !").when(ANY.alert(true)).weight(2),
+    line("Tu as vu ça ?").when(ANY.near_guards(true)).weight(2),
+];
+
+static SEE_DISGUISED_LINES: &[Line] = &[
+    line("Qui êtes-vous ?"),
+    line("Je ne vous connais pas !"),
+    line("On se connaît ?"),
+    line("Attendez..."),
+    line("Hé..."),
+    line("Montrez-moi votre visage..."),
+    line("Vous êtes d'ici ?"),
+    line("Vous êtes... ?"),
+    line("Vous êtes nouveau ?"),
+    line("Vous gardez quelle porte ?").when(ANY.guard_kind(GuardKind::Outer).disguise(Disguise::AsSameKind)).weight(3),
+    line("Depuis quand travaillez-vous à l'intérieur ?").when(ANY.guard_kind(GuardKind::Inner).disguise(Disguise::AsSameKind)).weight(3),
+];
+
+static HEAR_LINES: &[Line] = &[
+    line("Hein ?"),
+    line("Quoi ?"),
+    line("Écoutez !"),
+    line("Un bruit..."),
+    line("J'ai entendu quelque chose."),
+    line("Hmm..."),
+    line("Qui va là ?"),
+    line("C'est quoi, ce bruit ?"),
+    line("J'entends quelque chose..."),
+    line("Il y a quelqu'un ?"),
+    line("Tu as entendu ?").when(ANY.near_guards(true)).weight(2),
+    line("Ce n'est pas un rat...").when(ANY.wary(true)).weight(2),
+    line("Là, encore !").when(ANY.alert(true)).weight(2),
+];
+
+static HEAR_GUARD_LINES: &[Line] = &[
+    line("Où ça ?"),
+    line("J'arrive !"),
+    line("Me voilà !"),
+    line("Aux armes !"),
+    line("Où est-il ?"),
+];
+
+static CHASE_LINES: &[Line] = &[
+    line("Halte !"),
+    line("Hé !"),
+    line("Ah ah !"),
+    line("Je te vois !"),
+    line("J'arrive !"),
+    line("Je vais t'avoir !"),
+    line("Attends un peu..."),
+    line("Tu ne m'échapperas pas !"),
+    line("Oh que non..."),
+    line("Attrapez-le !"),
+    line("Après lui !"),
+    line("Au voleur !"),
+    line("Encore toi !").when(ANY.wary(true)).weight(2),
+    line("À l'aide, par ici !").when(ANY.near_guards(true)).weight(2),
+    line("Un intrus !").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static INVESTIGATE_LINES: &[Line] = &[
+    line("Encore ce bruit..."),
+    line("Je l'ai encore entendu !"),
+    line("Il y a quelqu'un !"),
+    line("Qui ça peut être ?"),
+    line("Ça recommence !"),
+    line("C'était quoi, ça ?"),
+    line("Je ferais mieux de vérifier..."),
+    line("Qu'est-ce qui fait tout ce bruit ?"),
+    line("J'espère que ce sont des rats !"),
+    line("Encore ?"),
+    line("Pas cette fois...").when(ANY.wary(true)).weight(2),
+];
+
+static END_CHASE_LINES: &[Line] = &[
+    line("(souffle, souffle)"),
+    line("Où est-il passé ?"),
+    line("Je l'ai perdu !"),
+    line("Disparu !"),
+    line("Reviens !"),
+    line("Argh !"),
+    line("Il ne reviendra pas."),
+    line("Zut !"),
+    line("La prochaine fois !"),
+    line("Quelle anguille !").when(ANY.wary(true)).weight(2),
+];
+
+static END_INVESTIGATION_LINES: &[Line] = &[
+    line("Ce n'était rien, sans doute."),
+    line("Qu'est-ce que ça pouvait être ?"),
+    line("Je retourne à mon poste."),
+    line("C'est calme, maintenant."),
+    line("C'est ici que je l'ai entendu..."),
+    line("Plus rien, maintenant."),
+];
+
+static DONE_LOOKING_LINES: &[Line] = &[
+    line("Sans doute des rats."),
+    line("Trop de café !"),
+    line("J'ai les nerfs."),
+    line("Sans doute rien."),
+    line("J'ai cru voir quelque chose."),
+    line("Tant pis."),
+    line("Rien."),
+    line("Je ne le vois plus."),
+    line("Je suis debout depuis trop longtemps.").when(ANY.after_turn(300)),
+    line("Je vois des choses, on dirait."),
+    line("J'espère que ce n'était rien."),
+    line("J'ai rêvé ?"),
+    line("Vivement la fin de mon service.").when(ANY.after_turn(300)).weight(2),
+    line("Il est encore là, quelque part.").when(ANY.wary(true)).weight(2),
+    line("Où est-ce passé ?").when(ANY.alert(true)).weight(2),
+];
+
+static DONE_SEEING_DISGUISED_LINES: &[Line] = &[
+    line("C'était qui ?"),
+    line("Hein..."),
+    line("Je me demande qui c'était."),
+    line("Tant pis."),
+    line("Je vois des choses."),
+    line("Je suis debout depuis trop longtemps.").when(ANY.after_turn(300)),
+    line("Je vois des choses, on dirait."),
+    line("Sans doute un nouveau."),
+    line("Au travail."),
+    line("J'ai rêvé ?"),
+    line("Je devrais prévenir le patron ?"),
+];
+
+static DONE_LISTENING_LINES: &[Line] = &[
+    line("Sans doute des rats."),
+    line("Trop de café !"),
+    line("J'ai les nerfs."),
+    line("Sans doute rien."),
+    line("J'ai cru entendre quelque chose."),
+    line("Tant pis."),
+    line("Rien."),
+    line("Je ne l'entends plus."),
+    line("Je suis debout depuis trop longtemps.").when(ANY.after_turn(300)),
+    line("J'entends des choses, on dirait."),
+    line("J'espère que ce n'était rien."),
+    line("J'ai rêvé ?"),
+    line("Je sais que tu es là...").when(ANY.wary(true)).weight(2),
+];
+
+static NOTICE_MISSING_LOOT_LINES: &[Line] = &[
+    line("Où est l'or ?"),
+    line("On a été volés !"),
+    line("Les pièces ont disparu !"),
+    line("Quelqu'un vole ici !"),
+    line("Il n'y avait pas de l'or, ici ?"),
+    line("Un voleur dans la maison !"),
+    line("Qui a pris le butin ?"),
+    line("Le patron va me tuer !"),
+    line("L'or du maître !").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static END_SEARCH_LINES: &[Line] = &[
+    line("Aucune trace de personne."),
+    line("Qui que ce soit, il est parti."),
+    line("Le patron ne va pas aimer ça."),
+    line("Je garde l'œil ouvert."),
+    line("Disparu sans laisser de trace."),
+    line("Il faudra le signaler."),
+];
+
+static DAMAGE_LINES: &[Line] = &[
+    line("Ouf !"),
+    line("Krak !"),
+    line("Paf !"),
+    line("Argh !"),
+    line("Vlan !"),
+    line("Bim !"),
+];
+
+static EXCHANGES: &[Exchange] = &[
+    exchange(&["Nuit calme.", "Trop calme."]),
+    exchange(&["Rien à signaler ?", "Rien pour l'instant.", "Garde l'œil ouvert."]),
+    exchange(&["Tu as vu quelque chose ?", "Juste des rats.", "Cet endroit en est plein."]),
+    exchange(&["Il fait froid, dehors.", "Mieux vaut ça que le patron, dedans."]).when(ANY.guard_kind(GuardKind::Outer)),
+    exchange(&["Qui surveille l'or ?", "Toi, je croyais.", "... Ah."]).when(ANY.guard_kind(GuardKind::Inner)),
+    exchange(&["Des nouvelles du voleur ?", "Pas encore.", "Il est ici, quelque part.", "On le trouvera."]).when(ANY.wary(true)).weight(3),
+    exchange(&["Quand finit notre service ?", "Pas assez tôt."]).when(ANY.after_turn(300)).weight(2),
+    exchange(&["C'était qui, le nouveau ?", "Quel nouveau ?", "Laisse tomber."]).when(ANY.disguise(Disguise::AsSameKind)),
+];
@@ -0,0 +1,166 @@
+use crate::cell_grid::{CellType, ItemKind};
+use crate::dialogue::{Exchange, Line};
+use crate::guard::{GuardKind, GuardMode};
+use crate::key_bindings::{Action, Preset};
+use crate::narration::NarrationEvent;
+
+use std::fmt::Display;
+
+mod english;
+mod french;
+mod spanish;
+
+// User-facing text, looked up by message ID in the current language's bundle.
+// Bundles other than English may leave messages out; those fall back to English.
+//
+// Messages with arguments contain numbered placeholders ({0}, {1}, ...) so that
+// translations can put the arguments in whatever order reads naturally.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    English = 0,
+    French = 1,
+    Spanish = 2,
+}
+
+pub const LANGUAGES: [Language; 3] = [
+    Language::English,
+    Language::French,
+    Language::Spanish,
+];
+
+pub fn language_from_index(index: i32) -> Option<Language> {
+    LANGUAGES.iter().find(|language| **language as i32 == index).copied()
+}
+
+#[derive(Clone, Copy)]
+pub enum Msg {
+    // Names of things
+    CellType(CellType),
+    ItemKind(ItemKind),
+    GuardKind(GuardKind),
+    GuardMode(GuardMode),
+    Action(Action),
+    Preset(Preset),
+    Narration(NarrationEvent),
+    North,
+    Northeast,
+    East,
+    Southeast,
+    South,
+    Southwest,
+    West,
+    Northwest,
+
+    // Sounds
+    Creak,
+
+    // Look mode
+    Unexplored,
+    CellLit, // {0}: cell type
+    CellDark, // {0}: cell type
+    CanHideHere,
+    You,
+    GuardFacing, // {0}: guard kind, {1}: direction
+    VisibleToGuard, // {0}: guard kind, lowercase; {1}: direction
+
+    // Message log
+    Someone,
+    Here,
+    DistanceAndDirection, // {0}: distance in cells, {1}: direction
+    LogLevelHeading, // {0}: level number
+    LogNarration, // {0}: turn, {1}: message
+    LogSpeech, // {0}: turn, {1}: speaker, {2}: place, {3}: message
+    LogEmpty,
+    More,
+
+    // Key bindings screen
+    KeyBindingsTitle,
+    PresetLabel,
+    PressKeyToBind,
+    NoKeys,
+
+    // Status bars
+    Health,
+    Air,
+    LevelSeen, // {0}: level number, {1}: percent seen
+    LootUnknownTotal, // {0}: loot collected
+    Loot, // {0}: loot collected, {1}: total loot
+    HelpPageNumber, // {0}: page, {1}: page count
+    HelpBarHint,
+    MessageLogBarHint,
+    OverviewBarHint,
+    KeyBindingsBarHint,
+    LookBarHint, // {0}: look keys
+    PressForHelp, // {0}: help keys
+
+    // Help pages. Paragraphs are wrapped to fit the help window when drawn. Every
+    // page takes the same placeholders, the keys for: {0} help, {1} the moves from
+    // north clockwise to northwest, {2} wait, {3} guard vision, {4} noise preview,
+    // {5} look, {6} zoom in, {7} zoom out, {8} zoom to fit, {9} minimap, {10}
+    // overview, {11} message log, {12} explore.
+    HelpPage(usize),
+}
+
+pub const HELP_PAGE_COUNT: usize = 3;
+
+// Sets of guard lines; a guard says a line from the appropriate set when its
+// mode changes. See dialogue.rs for how lines are chosen.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineSet {
+    See,
+    SeeDisguised,
+    Hear,
+    HearGuard,
+    Chase,
+    Investigate,
+    EndChase,
+    EndInvestigation,
+    DoneLooking,
+    DoneSeeingDisguised,
+    DoneListening,
+    Damage,
+    NoticeMissingLoot,
+    EndSearch,
+}
+
+pub fn text(language: Language, msg: Msg) -> &'static str {
+    let translated = match language {
+        Language::English => None,
+        Language::French => french::text(msg),
+        Language::Spanish => spanish::text(msg),
+    };
+
+    translated.unwrap_or_else(|| english::text(msg))
+}
+
+// Text with its placeholders filled in by args, in order
+
+pub fn format_text(language: Language, msg: Msg, args: &[&dyn Display]) -> String {
+    let mut s = String::from(text(language, msg));
+    for (i, arg) in args.iter().enumerate() {
+        s = s.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    s
+}
+
+pub fn lines(language: Language, line_set: LineSet) -> &'static [Line] {
+    let translated = match language {
+        Language::English => None,
+        Language::French => french::lines(line_set),
+        Language::Spanish => spanish::lines(line_set),
+    };
+
+    translated.unwrap_or_else(|| english::lines(line_set))
+}
+
+pub fn exchanges(language: Language) -> &'static [Exchange] {
+    let translated = match language {
+        Language::English => None,
+        Language::French => french::exchanges(),
+        Language::Spanish => spanish::exchanges(),
+    };
+
+    translated.unwrap_or_else(english::exchanges)
+}
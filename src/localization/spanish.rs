@@ -0,0 +1,461 @@
+use crate::cell_grid::{CellType, ItemKind};
+use crate::dialogue::{ANY, Disguise, Exchange, Line, exchange, line};
+use crate::guard::{GuardKind, GuardMode};
+use crate::key_bindings::{Action, Preset};
+use crate::narration::NarrationEvent;
+
+use super::{LineSet, Msg};
+
+pub fn text(msg: Msg) -> Option<&'static str> {
+    let s = match msg {
+        Msg::CellType(cell_type) => name_for_cell_type(cell_type),
+        Msg::ItemKind(kind) => name_for_item_kind(kind),
+        Msg::GuardKind(GuardKind::Outer) => "Guardia exterior",
+        Msg::GuardKind(GuardKind::Inner) => "Guardia interior",
+        Msg::GuardMode(mode) => description_for_guard_mode(mode),
+        Msg::Action(action) => name_for_action(action),
+        Msg::Preset(preset) => name_for_preset(preset),
+        Msg::Narration(event) => text_for_narration_event(event),
+        Msg::North => "norte",
+        Msg::Northeast => "noreste",
+        Msg::East => "este",
+        Msg::Southeast => "sureste",
+        Msg::South => "sur",
+        Msg::Southwest => "suroeste",
+        Msg::West => "oeste",
+        Msg::Northwest => "noroeste",
+
+        Msg::Creak => "\u{ab}cric\u{bb}",
+
+        Msg::Unexplored => "Sin explorar",
+        Msg::CellLit => "{0} (con luz)",
+        Msg::CellDark => "{0} (a oscuras)",
+        Msg::CanHideHere => "Puedes esconderte aquí",
+        Msg::You => "Tú",
+        Msg::GuardFacing => "{0}, mirando al {1}",
+        Msg::VisibleToGuard => "Visible para el {0} al {1}",
+
+        Msg::Someone => "Alguien",
+        Msg::Here => "aquí",
+        Msg::DistanceAndDirection => "{0} al {1}",
+        Msg::LogLevelHeading => "Nivel {0}",
+        Msg::LogNarration => "Turno {0}: {1}",
+        Msg::LogSpeech => "Turno {0}: {1} ({2}): {3}",
+        Msg::LogEmpty => "Todavía no se ha dicho ni oído nada.",
+        Msg::More => "(más)",
+
+        Msg::KeyBindingsTitle => "Asignación de teclas",
+        Msg::PresetLabel => "Esquema",
+        Msg::PressKeyToBind => "Pulsa una tecla para añadirla (Esc para cancelar)",
+        Msg::NoKeys => "(ninguna)",
+
+        Msg::Health => "Salud",
+        Msg::Air => "Aire",
+        Msg::LevelSeen => "Nivel {0}: {1}% visto",
+        Msg::LootUnknownTotal => "Botín {0}/?",
+        Msg::Loot => "Botín {0}/{1}",
+        Msg::HelpPageNumber => "Página {0} de {1}",
+        Msg::HelpBarHint => "Flechas izquierda/derecha (o toca un lado) para ver la ayuda, o Esc para cerrar",
+        Msg::MessageLogBarHint => "Registro: arriba/abajo o RePág/AvPág para desplazarte; Esc para cerrar",
+        Msg::OverviewBarHint => "Vista general: haz clic en una casilla conocida para examinarla; Esc para cerrar",
+        Msg::KeyBindingsBarHint => "Arriba/abajo para elegir, Intro para cambiar o añadir una tecla, Supr para borrar, Esc para cerrar",
+        Msg::LookBarHint => "Examinar: mueve el cursor con las teclas de movimiento. Intro para ir allí; {0} o Esc para terminar.",
+        Msg::PressForHelp => "Pulsa {0} para ver la ayuda",
+
+        Msg::HelpPage(0) => HELP_PAGE_INTRO,
+        Msg::HelpPage(1) => HELP_PAGE_HINTS,
+        Msg::HelpPage(_) => HELP_PAGE_TOOLS,
+    };
+
+    Some(s)
+}
+
+fn name_for_cell_type(cell_type: CellType) -> &'static str {
+    match cell_type {
+        CellType::GroundNormal     => "Suelo",
+        CellType::GroundGrass      => "Hierba",
+        CellType::GroundWater      => "Agua",
+        CellType::GroundMarble     => "Suelo de mármol",
+        CellType::GroundWood       => "Suelo de madera",
+        CellType::GroundWoodCreaky => "Suelo de madera que cruje",
+        CellType::Wall0000 |
+        CellType::Wall0001 |
+        CellType::Wall0010 |
+        CellType::Wall0011 |
+        CellType::Wall0100 |
+        CellType::Wall0101 |
+        CellType::Wall0110 |
+        CellType::Wall0111 |
+        CellType::Wall1000 |
+        CellType::Wall1001 |
+        CellType::Wall1010 |
+        CellType::Wall1011 |
+        CellType::Wall1100 |
+        CellType::Wall1101 |
+        CellType::Wall1110 |
+        CellType::Wall1111         => "Pared",
+        CellType::OneWayWindowE    => "Ventana de un solo sentido (salida al este)",
+        CellType::OneWayWindowW    => "Ventana de un solo sentido (salida al oeste)",
+        CellType::OneWayWindowN    => "Ventana de un solo sentido (salida al norte)",
+        CellType::OneWayWindowS    => "Ventana de un solo sentido (salida al sur)",
+        CellType::PortcullisNS     => "Rastrillo",
+        CellType::PortcullisEW     => "Rastrillo",
+        CellType::DoorNS           => "Umbral",
+        CellType::DoorEW           => "Umbral",
+    }
+}
+
+fn name_for_item_kind(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Chair => "Silla",
+        ItemKind::Table => "Mesa",
+        ItemKind::Bush => "Arbusto",
+        ItemKind::Coin => "Moneda de oro",
+        ItemKind::DoorNS => "Puerta",
+        ItemKind::DoorEW => "Puerta",
+        ItemKind::PortcullisNS => "Rastrillo",
+        ItemKind::PortcullisEW => "Rastrillo",
+        ItemKind::Outfit(None) => "Tu propia ropa",
+        ItemKind::Outfit(Some(GuardKind::Outer)) => "Uniforme de guardia exterior",
+        ItemKind::Outfit(Some(GuardKind::Inner)) => "Uniforme de guardia interior",
+    }
+}
+
+fn description_for_guard_mode(mode: GuardMode) -> &'static str {
+    match mode {
+        GuardMode::Patrol => "Patrullando",
+        GuardMode::Look => "Mirando algo",
+        GuardMode::LookAtDisguised => "Mirando a un desconocido",
+        GuardMode::Listen => "Escuchando",
+        GuardMode::ChaseVisibleTarget => "Persiguiendo al ladrón",
+        GuardMode::MoveToLastSighting => "Buscando donde vio al ladrón",
+        GuardMode::MoveToLastSound => "Investigando un ruido",
+        GuardMode::MoveToGuardShout => "Respondiendo a un grito",
+        GuardMode::SearchForMissingLoot => "Buscando el botín robado",
+    }
+}
+
+fn name_for_action(action: Action) -> &'static str {
+    match action {
+        Action::MoveN => "Mover al norte",
+        Action::MoveNE => "Mover al noreste",
+        Action::MoveE => "Mover al este",
+        Action::MoveSE => "Mover al sureste",
+        Action::MoveS => "Mover al sur",
+        Action::MoveSW => "Mover al suroeste",
+        Action::MoveW => "Mover al oeste",
+        Action::MoveNW => "Mover al noroeste",
+        Action::Wait => "Esperar",
+        Action::Help => "Ayuda",
+        Action::ToggleMessages => "Mostrar/ocultar diálogos",
+        Action::ToggleGuardVision => "Mostrar/ocultar visión de guardias",
+        Action::ToggleNoisePreview => "Mostrar/ocultar alcance del ruido",
+        Action::Look => "Examinar",
+        Action::Explore => "Explorar",
+        Action::ZoomIn => "Acercar",
+        Action::ZoomOut => "Alejar",
+        Action::ZoomToFit => "Ajustar a la pantalla",
+        Action::ToggleMinimap => "Mostrar/ocultar minimapa",
+        Action::Overview => "Vista general del mapa",
+        Action::MessageLog => "Registro de mensajes",
+    }
+}
+
+fn name_for_preset(preset: Preset) -> &'static str {
+    match preset {
+        Preset::Standard => "Estándar (teclado numérico, teclas vi)",
+        Preset::ViKeys => "Teclas vi",
+        Preset::Numpad => "Teclado numérico",
+        Preset::Wasd => "WASD + QEZC",
+    }
+}
+
+fn text_for_narration_event(event: NarrationEvent) -> &'static str {
+    match event {
+        NarrationEvent::LevelStart(0) => "Recoge las monedas de oro y descubre toda la mansión.",
+        NarrationEvent::LevelStart(1) => "¡Cuidado con el guardia que patrulla!",
+        NarrationEvent::LevelStart(_) => "Otra mansión. Llévate el oro y recorre cada habitación.",
+        NarrationEvent::LastCoinTaken => "Ese era el último oro. Ahora termina de recorrer la mansión.",
+        NarrationEvent::AllSeen => "Has visto toda la mansión. Ahora encuentra el resto del oro.",
+        NarrationEvent::ExitReachable => "¡Mansión vaciada! Sal por el borde del mapa para escapar.",
+        NarrationEvent::AlarmRaised => "¡Te han visto!",
+        NarrationEvent::DisguiseBlown => "¡Han descubierto tu disfraz!",
+        NarrationEvent::PlayerDied => "¡Has muerto! Pulsa Ctrl+R o toca el mapa para empezar de nuevo.",
+    }
+}
+
+static HELP_PAGE_INTRO: &str =
+"Disguiser
+
+Pulsa la flecha derecha para ver consejos, o {0} para mostrar u ocultar esta ayuda
+
+Cuélate en mansiones, recórrelas, roba todo el botín y sal de allí.
+
+¡Los guardias no pueden ser heridos! Tampoco pueden cortar esquinas en diagonal.
+
+Muévete al norte, noreste, este, sureste, sur, suroeste, oeste y noroeste con {1}, y pulsa {2} para esperar. Las flechas con Mayús/Ctrl más Izquierda/Derecha también sirven para moverte en diagonal. Pulsa Esc para cambiar las teclas.
+
+La salud aparece en la barra de estado, abajo a la izquierda.
+
+Un juego de James McNeill para el Seven-day Roguelike Challenge de 2021
+
+Agradecimientos: Mendi Carroll, Mike Gaffney
+
+mcneja.github.io
+playtechs.blogspot.com";
+
+static HELP_PAGE_HINTS: &str =
+"Consejos
+
+Recoge las monedas de oro pasando por encima.
+
+¡El movimiento en diagonal es fundamental! Los guardias no pueden cortar esquinas, así que rodearlas en diagonal es la clave para sacarles ventaja.
+
+Los guardias solo ven lo que tienen delante.
+
+Si un guardia te ve y está a tu lado, ¡te atacará!
+
+Los arbustos, las mesas y el agua sirven de escondite. Los guardias que patrullan no pueden verte cuando estás escondido. Los guardias alerta (con un signo de interrogación sobre la cabeza) pueden verte si están a tu lado.
+
+Las ventanas altas de un solo sentido permiten escapar rápido. ¡Los guardias no pueden usarlas!
+
+Fuera de la mansión, en la oscuridad, los guardias no ven tan lejos.";
+
+static HELP_PAGE_TOOLS: &str =
+"Herramientas
+
+Pulsa {3} para mostrar lo que ven los guardias: amarillo donde hay luz, azul donde está oscuro.
+
+Los suelos que crujen hacen ruido. Pulsa {4} para mostrar hasta dónde llegaría un ruido desde donde estás y qué guardias lo oirían.
+
+Pulsa {5} para examinar el mapa. Mueve el cursor con las teclas de movimiento y pulsa {5} o Esc al terminar, o Intro para ir hasta el cursor.
+
+Pulsa {6} y {7} para acercar o alejar, o {8} para ajustar la vista a la pantalla. Pulsa {9} para mostrar u ocultar el minimapa, y {10} para ver todo el mapa. Pulsa {11} para repasar lo que se ha dicho y oído.
+
+Pulsa {12} para explorar automáticamente. Los desplazamientos y la exploración se detienen en cuanto aparece un guardia o algo hace ruido.
+
+Con ratón o pantalla táctil, haz clic en una casilla para ir allí, y clic derecho (o Mayús+clic) para examinarla. Toca la barra superior para ver la ayuda.";
+
+pub fn lines(line_set: LineSet) -> Option<&'static [Line]> {
+    let lines = match line_set {
+        LineSet::See => SEE_LINES,
+        LineSet::SeeDisguised => SEE_DISGUISED_LINES,
+        LineSet::Hear => HEAR_LINES,
+        LineSet::HearGuard => HEAR_GUARD_LINES,
+        LineSet::Chase => CHASE_LINES,
+        LineSet::Investigate => INVESTIGATE_LINES,
+        LineSet::EndChase => END_CHASE_LINES,
+        LineSet::EndInvestigation => END_INVESTIGATION_LINES,
+        LineSet::DoneLooking => DONE_LOOKING_LINES,
+        LineSet::DoneSeeingDisguised => DONE_SEEING_DISGUISED_LINES,
+        LineSet::DoneListening => DONE_LISTENING_LINES,
+        LineSet::Damage => DAMAGE_LINES,
+        LineSet::NoticeMissingLoot => NOTICE_MISSING_LOOT_LINES,
+        LineSet::EndSearch => END_SEARCH_LINES,
+    };
+
+    Some(lines)
+}
+
+pub fn exchanges() -> Option<&'static [Exchange]> {
+    Some(EXCHANGES)
+}
+
+static SEE_LINES: &[Line] = &[
+    line("¿Quién anda ahí?"),
+    line("¿Eh?"),
+    line("¿Qué?"),
+    line("Espera..."),
+    line("¿Quién es?"),
+    line("Oye..."),
+    line("Mmm..."),
+    line("¿Qué se ha movido?"),
+    line("¿Se ha movido esa sombra?"),
+    line("Veo algo..."),
+    line("¿Hola?"),
+    line("Otra vez no...").when(ANY.wary(true)).weight(2),
+    line("¡Muéstrate!").when(ANY.wary(true)).weight(2),
+    line("¡Otra vez ahí!").when(ANY.alert(true)).weight(2),
+    line("¿Has visto eso?").when(ANY.near_guards(true)).weight(2),
+];
+
+static SEE_DISGUISED_LINES: &[Line] = &[
+    line("¿Quién eres?"),
+    line("¡No me suenas!"),
+    line("¿Te conozco?"),
+    line("Espera..."),
+    line("Oye..."),
+    line("Déjame verte la cara..."),
+    line("¿Tú trabajas aquí?"),
+    line("¿Tú eres...?"),
+    line("¿Eres nuevo?"),
+    line("¿Qué puerta vigilas?").when(ANY.guard_kind(GuardKind::Outer).disguise(Disguise::AsSameKind)).weight(3),
+    line("¿Desde cuándo trabajas dentro?").when(ANY.guard_kind(GuardKind::Inner).disguise(Disguise::AsSameKind)).weight(3),
+];
+
+static HEAR_LINES: &[Line] = &[
+    line("¿Eh?"),
+    line("¿Qué?"),
+    line("¡Escucha!"),
+    line("Un ruido..."),
+    line("He oído algo."),
+    line("Mmm..."),
+    line("¿Quién anda ahí?"),
+    line("¿Qué es ese ruido?"),
+    line("Oigo algo..."),
+    line("¿Hola?"),
+    line("¿Has oído eso?").when(ANY.near_guards(true)).weight(2),
+    line("Eso no es una rata...").when(ANY.wary(true)).weight(2),
+    line("¡Ahí, otra vez!").when(ANY.alert(true)).weight(2),
+];
+
+static HEAR_GUARD_LINES: &[Line] = &[
+    line("¿Dónde?"),
+    line("¡Voy!"),
+    line("¡Ya voy!"),
+    line("¡A las armas!"),
+    line("¿Dónde está?"),
+];
+
+static CHASE_LINES: &[Line] = &[
+    line("¡Alto!"),
+    line("¡Eh!"),
+    line("¡Ajá!"),
+    line("¡Te veo!"),
+    line("¡Voy!"),
+    line("¡Te voy a pillar!"),
+    line("Ya verás..."),
+    line("¡No te escaparás!"),
+    line("Ah, no, de eso nada..."),
+    line("¡A por él!"),
+    line("¡Tras él!"),
+    line("¡Al ladrón!"),
+    line("¡Tú otra vez!").when(ANY.wary(true)).weight(2),
+    line("¡Ayuda, aquí!").when(ANY.near_guards(true)).weight(2),
+    line("¡Un intruso!").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static INVESTIGATE_LINES: &[Line] = &[
+    line("Otra vez ese ruido..."),
+    line("¡Lo he vuelto a oír!"),
+    line("¡Hay alguien ahí!"),
+    line("¿Quién podrá ser?"),
+    line("¡Otra vez!"),
+    line("¿Qué ha sido eso?"),
+    line("Mejor voy a mirar..."),
+    line("¿Qué hace tanto ruido?"),
+    line("¡Más vale que sean ratas!"),
+    line("¿Otra vez?"),
+    line("Esta vez no...").when(ANY.wary(true)).weight(2),
+];
+
+static END_CHASE_LINES: &[Line] = &[
+    line("(jadeo, jadeo)"),
+    line("¿Adónde ha ido?"),
+    line("¡Lo he perdido!"),
+    line("¡Se ha ido!"),
+    line("¡Vuelve!"),
+    line("¡Argh!"),
+    line("No va a volver."),
+    line("¡Maldición!"),
+    line("¡La próxima vez!"),
+    line("¡Qué escurridizo!").when(ANY.wary(true)).weight(2),
+];
+
+static END_INVESTIGATION_LINES: &[Line] = &[
+    line("Supongo que no era nada."),
+    line("¿Qué sería?"),
+    line("Mejor vuelvo."),
+    line("Ahora está tranquilo."),
+    line("Aquí es donde lo oí..."),
+    line("Ya no hay nada."),
+];
+
+static DONE_LOOKING_LINES: &[Line] = &[
+    line("Serían ratas."),
+    line("¡Demasiado café!"),
+    line("Estoy nervioso."),
+    line("Seguramente nada."),
+    line("Me pareció ver algo."),
+    line("Bueno."),
+    line("Nada."),
+    line("Ya no lo veo."),
+    line("Llevo demasiado tiempo despierto.").when(ANY.after_turn(300)),
+    line("Estaré viendo cosas."),
+    line("Espero que no fuera nada."),
+    line("¿Me lo he imaginado?"),
+    line("Que acabe ya mi turno.").when(ANY.after_turn(300)).weight(2),
+    line("Sigue por aquí, en alguna parte.").when(ANY.wary(true)).weight(2),
+    line("¿Adónde se ha ido?").when(ANY.alert(true)).weight(2),
+];
+
+static DONE_SEEING_DISGUISED_LINES: &[Line] = &[
+    line("¿Quién era ese?"),
+    line("Mmm..."),
+    line("¿Quién sería?"),
+    line("Bueno."),
+    line("Estoy viendo cosas."),
+    line("Llevo demasiado tiempo despierto.").when(ANY.after_turn(300)),
+    line("Estaré viendo cosas."),
+    line("Será nuevo."),
+    line("Mejor vuelvo al trabajo."),
+    line("¿Me lo he imaginado?"),
+    line("¿Debería avisar al jefe?"),
+];
+
+static DONE_LISTENING_LINES: &[Line] = &[
+    line("Serían ratas."),
+    line("¡Demasiado café!"),
+    line("Estoy nervioso."),
+    line("Seguramente nada."),
+    line("Me pareció oír algo."),
+    line("Bueno."),
+    line("Nada."),
+    line("Ya no lo oigo."),
+    line("Llevo demasiado tiempo despierto.").when(ANY.after_turn(300)),
+    line("Estaré oyendo cosas."),
+    line("Espero que no fuera nada."),
+    line("¿Me lo he imaginado?"),
+    line("Sé que estás ahí...").when(ANY.wary(true)).weight(2),
+];
+
+static NOTICE_MISSING_LOOT_LINES: &[Line] = &[
+    line("¿Dónde está el oro?"),
+    line("¡Nos han robado!"),
+    line("¡Las monedas han desaparecido!"),
+    line("¡Alguien está robando!"),
+    line("¿No había oro aquí?"),
+    line("¡Hay un ladrón en la casa!"),
+    line("¿Quién se ha llevado el botín?"),
+    line("¡El jefe me va a matar!"),
+    line("¡El oro del amo!").when(ANY.guard_kind(GuardKind::Inner)).weight(2),
+];
+
+static END_SEARCH_LINES: &[Line] = &[
+    line("Ni rastro de nadie."),
+    line("Quien fuera, ya se ha ido."),
+    line("Al jefe no le va a gustar."),
+    line("Estaré atento."),
+    line("Desaparecido sin dejar rastro."),
+    line("Tendré que informar de esto."),
+];
+
+static DAMAGE_LINES: &[Line] = &[
+    line("¡Uf!"),
+    line("¡Krak!"),
+    line("¡Pum!"),
+    line("¡Ay!"),
+    line("¡Zas!"),
+    line("¡Paf!"),
+];
+
+static EXCHANGES: &[Exchange] = &[
+    exchange(&["Noche tranquila.", "Demasiado tranquila."]),
+    exchange(&["¿Algo que informar?", "Nada aún.", "Mantén los ojos abiertos."]),
+    exchange(&["¿Has visto algo?", "Solo ratas.", "Este sitio está lleno."]),
+    exchange(&["Hace frío aquí fuera.", "Mejor que dentro con el jefe."]).when(ANY.guard_kind(GuardKind::Outer)),
+    exchange(&["¿Quién vigila el oro?", "Tú, creía.", "...Ah."]).when(ANY.guard_kind(GuardKind::Inner)),
+    exchange(&["¿Alguna señal del ladrón?", "Aún no.", "Está aquí, en alguna parte.", "Lo encontraremos."]).when(ANY.wary(true)).weight(3),
+    exchange(&["¿Cuándo acaba nuestro turno?", "No lo bastante pronto."]).when(ANY.after_turn(300)).weight(2),
+    exchange(&["¿Quién era ese nuevo?", "¿Qué nuevo?", "Olvídalo."]).when(ANY.disguise(Disguise::AsSameKind)),
+];
@@ -0,0 +1,73 @@
+use crate::coord::Coord;
+use crate::render::{Quad, Renderer};
+
+// The map's cells, kept between frames as one quad per cell. Each frame the
+// game sets every cell's look, but only cells whose look changed are sent to
+// Javascript, which keeps the rest of the layer from the frame before.
+
+pub struct MapLayer {
+    size: Coord,
+    quads: Vec<Quad>, // indexed by x * size_y + y
+    dirty: Vec<u32>, // quads changed since the layer was last drawn
+    rebuild: bool, // send all quads next time, e.g. after the map size changes
+}
+
+// Quads for cells that aren't drawn are fully transparent.
+const EMPTY_QUAD: Quad = Quad {
+    dest_x: 0,
+    dest_y: 0,
+    size_x: 0,
+    size_y: 0,
+    color: 0,
+    texture_index: 0,
+    src_x: 0,
+    src_y: 0,
+    src_size_x: 0,
+    src_size_y: 0,
+};
+
+pub fn new_map_layer() -> MapLayer {
+    MapLayer {
+        size: Coord(0, 0),
+        quads: Vec::new(),
+        dirty: Vec::new(),
+        rebuild: true,
+    }
+}
+
+impl MapLayer {
+    pub fn resize(&mut self, size: Coord) {
+        if size == self.size {
+            return;
+        }
+
+        self.size = size;
+        self.quads.clear();
+        self.quads.resize((size.0 * size.1) as usize, EMPTY_QUAD);
+        self.dirty.clear();
+        self.rebuild = true;
+    }
+
+    // Set how a cell looks, or None to leave it blank. The quad's position is in
+    // unscaled pixels relative to the map's origin.
+
+    pub fn set_cell(&mut self, pos: Coord, quad: Option<Quad>) {
+        let i = (pos.0 * self.size.1 + pos.1) as usize;
+        let quad = quad.unwrap_or(EMPTY_QUAD);
+        if self.quads[i] != quad {
+            self.quads[i] = quad;
+            if !self.rebuild {
+                self.dirty.push(i as u32);
+            }
+        }
+    }
+
+    pub fn draw(&mut self, renderer: &mut dyn Renderer, view_offset: Coord, scale: i32) {
+        if self.rebuild {
+            self.dirty.clear();
+        }
+        renderer.draw_map_layer(&self.quads, &self.dirty, self.rebuild, view_offset, scale);
+        self.dirty.clear();
+        self.rebuild = false;
+    }
+}
@@ -0,0 +1,46 @@
+use crate::coord::Coord;
+use crate::speech_bubbles::PopupType;
+
+use std::collections::VecDeque;
+
+// Everything said or heard during a game, so the player can look back over a busy turn
+
+const MESSAGE_LOG_ENTRIES_MAX: usize = 200;
+
+pub struct LogEntry {
+    pub level: usize,
+    pub turn: usize,
+    pub popup_type: PopupType,
+    pub speaker: &'static str,
+    pub offset: Coord, // from the player, at the time
+    pub msg: &'static str,
+}
+
+pub struct MessageLog {
+    entries: VecDeque<LogEntry>,
+}
+
+pub fn new_message_log() -> MessageLog {
+    MessageLog {
+        entries: VecDeque::new(),
+    }
+}
+
+impl MessageLog {
+    pub fn add(&mut self, entry: LogEntry) {
+        if self.entries.len() >= MESSAGE_LOG_ENTRIES_MAX {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Oldest first
+
+    pub fn iter(&self) -> impl Iterator<Item=&LogEntry> {
+        self.entries.iter()
+    }
+}
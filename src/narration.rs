@@ -0,0 +1,12 @@
+// Level events worth announcing to the player
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum NarrationEvent {
+    LevelStart(usize),
+    LastCoinTaken,
+    AllSeen,
+    ExitReachable,
+    AlarmRaised,
+    DisguiseBlown,
+    PlayerDied,
+}
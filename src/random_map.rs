@@ -2,6 +2,7 @@ use crate::cell_grid;
 use crate::cell_grid::{Cell, CellGrid, CellType, INVALID_REGION, Item, ItemKind, Map, PatrolRegion, Random, Rect};
 use crate::coord::Coord;
 use crate::guard;
+use crate::tiles;
 
 use rand::prelude::{Rng, SliceRandom};
 use std::cmp::{min, max};
@@ -95,14 +96,18 @@ fn generate_siheyuan(random: &mut Random, level: usize) -> Map {
 
     // Create exits connecting rooms.
 
+    let earshot = cell_grid::new_earshot(cells.extents()[0], cells.extents()[1]);
+
     let mut map = Map {
         cells: cells,
         items: Vec::new(),
         patrol_regions: Vec::new(),
         patrol_routes: Vec::new(),
+        region_distance_fields: Vec::new(),
         guards: Vec::new(),
         pos_start: Coord(0, 0),
         total_loot: 0,
+        earshot,
     };
 
     let (rooms, adjacencies, pos_start) = create_exits(
@@ -133,9 +138,11 @@ fn generate_siheyuan(random: &mut Random, level: usize) -> Map {
     place_exterior_bushes(random, &mut map);
     place_front_pillars(&mut map);
 
-    // Place guards.
+    // Compute move costs and guard pathing before placing guards.
+
+    cache_cell_info(&mut map);
 
-//  init_pathing(map);
+    // Place guards.
 
     if level > 0 {
         place_guards_by_type(random, level, &rooms, &mut map, guard::GuardKind::Inner);
@@ -144,8 +151,6 @@ fn generate_siheyuan(random: &mut Random, level: usize) -> Map {
 
     mark_exterior_as_seen(&mut map);
 
-    cache_cell_info(&mut map);
-
     map.total_loot = map.items.iter().filter(|&item| item.kind == ItemKind::Coin).count();
 
     map
@@ -1928,10 +1933,12 @@ fn place_guard(random: &mut Random, map: &mut Map, pos: Coord, kind: guard::Guar
         mode_timeout: 0,
         region_goal: INVALID_REGION,
         region_prev: INVALID_REGION,
+        memory: guard::new_guard_memory(),
     };
 
     guard.setup_goal_region(random, map);
     guard.dir = guard.initial_dir(map);
+    guard.memory.learn_loot(map);
 
     map.guards.push(guard);
 }
@@ -1965,29 +1972,26 @@ fn cache_cell_info(map: &mut Map) {
         for y in 0..sy {
             let cell = &mut map.cells[[x, y]];
             let cell_type = cell.cell_type;
-            let tile = cell_grid::tile_def(cell_type);
-            cell.move_cost = cell_grid::guard_move_cost_for_tile_type(cell_type);
+            let tile = tiles::tile_def(cell_type);
+            cell.move_cost = tile.guard_move_cost;
             cell.blocks_player_sight = tile.blocks_player_sight;
             cell.blocks_sight = tile.blocks_sight;
             cell.blocks_sound = tile.blocks_sound;
-            cell.hides_player = false;
+            cell.hides_player = tile.hides_player;
         }
     }
 
     for item in &map.items {
         let cell = &mut map.cells[[item.pos.0 as usize, item.pos.1 as usize]];
-        let kind = item.kind;
-        cell.move_cost = max(cell.move_cost, cell_grid::guard_move_cost_for_item_kind(kind));
-        if kind == ItemKind::DoorNS || kind == ItemKind::DoorEW {
-            cell.blocks_player_sight = true;
-        }
-        if kind == ItemKind::DoorNS || kind == ItemKind::DoorEW || kind == ItemKind::PortcullisNS || kind == ItemKind::PortcullisEW || kind == ItemKind::Bush {
-            cell.blocks_sight = true;
-        }
-        if kind == ItemKind::Table || kind == ItemKind::Bush {
-            cell.hides_player = true;
-        }
+        let item_tile = tiles::item_def(item.kind);
+        cell.move_cost = max(cell.move_cost, item_tile.guard_move_cost);
+        cell.blocks_player_sight |= item_tile.blocks_player_sight;
+        cell.blocks_sight |= item_tile.blocks_sight;
+        cell.blocks_sound |= item_tile.blocks_sound;
+        cell.hides_player |= item_tile.hides_player;
     }
+
+    map.update_region_distance_fields();
 }
 
 fn non_dead_end_rooms<F>(rooms: &[Room], adjacencies: &[Adjacency], accept_room: F) -> Vec<bool> where F: Fn(&Room) -> bool {
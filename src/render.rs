@@ -0,0 +1,170 @@
+use crate::coord::Coord;
+
+// Drawing. Each frame the game draws through a Renderer: in the browser that's
+// engine::WasmRenderer, which batches quads for WebGL, and SoftwareRenderer
+// draws them into an RGBA buffer in memory, for screenshots and tests without a
+// GPU. Screen coordinates are in pixels with y pointing up.
+
+/// A rectangular area of a texture, stretched to a rectangle on the screen and
+/// multiplied by a color. The layout is shared with game.js.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Quad {
+    pub dest_x: i32,
+    pub dest_y: i32,
+    pub size_x: i32,
+    pub size_y: i32,
+    pub color: u32,
+    pub texture_index: u32,
+    pub src_x: i32,
+    pub src_y: i32,
+    pub src_size_x: i32,
+    pub src_size_y: i32,
+}
+
+/// Texture index for a solid white texture, to fill rectangles with
+pub const TEXTURE_INDEX_WHITE: u32 = u32::MAX;
+
+pub trait Renderer {
+    fn draw_quad(&mut self, quad: Quad);
+
+    /// Draw a layer of quads that may be kept between frames. Positions are in
+    /// unscaled pixels, and are scaled and then offset on the screen. Unless
+    /// rebuild is set, only the quads listed in dirty have changed since the last
+    /// call.
+    fn draw_map_layer(&mut self, quads: &[Quad], dirty: &[u32], rebuild: bool, offset: Coord, scale: i32);
+
+    /// Fill a rectangle with a solid color
+    fn draw_rect(&mut self, dest_x: i32, dest_y: i32, size_x: i32, size_y: i32, color: u32) {
+        self.draw_quad(Quad { dest_x, dest_y, size_x, size_y, color, texture_index: TEXTURE_INDEX_WHITE, src_x: 0, src_y: 0, src_size_x: size_x, src_size_y: size_y });
+    }
+
+    /// Draw a character of text, given the quad for its glyph in the font
+    /// texture. line_start is where the line of text it belongs to was started;
+    /// renderers that show characters rather than glyphs, like the terminal's,
+    /// use it to keep the characters of a line together.
+    fn draw_char(&mut self, _c: char, _line_start: Coord, quad: Quad) {
+        self.draw_quad(quad);
+    }
+}
+
+// Software rendering
+
+/// An image in memory, with pixels in 0xAABBGGRR form. Rows run from the top
+/// down, as in image files. Texture source rectangles are measured the same way
+/// as in game.js, where the y axis is flipped: a quad's top edge shows the
+/// texture's row src_y.
+pub struct Image {
+    pub size_x: i32,
+    pub size_y: i32,
+    pub pixels: Vec<u32>,
+}
+
+pub fn new_image(size_x: i32, size_y: i32, color: u32) -> Image {
+    Image {
+        size_x,
+        size_y,
+        pixels: vec![color; (size_x * size_y) as usize],
+    }
+}
+
+impl Image {
+    /// Bytes in R, G, B, A order, rows from the top down
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|pixel| pixel.to_le_bytes()).collect()
+    }
+}
+
+/// Draws quads into an opaque image, blending as WebGL does with
+/// SRC_ALPHA, ONE_MINUS_SRC_ALPHA. Textures are indexed as in game.js, and any
+/// index past the end is solid white.
+pub struct SoftwareRenderer {
+    pub screen: Image,
+    pub textures: Vec<Image>,
+}
+
+pub const OPAQUE_BLACK: u32 = 0xff000000;
+
+pub fn new_software_renderer(size_x: i32, size_y: i32, textures: Vec<Image>) -> SoftwareRenderer {
+    SoftwareRenderer {
+        screen: new_image(size_x, size_y, OPAQUE_BLACK),
+        textures,
+    }
+}
+
+impl SoftwareRenderer {
+    pub fn clear(&mut self) {
+        self.screen.pixels.fill(OPAQUE_BLACK);
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn draw_quad(&mut self, quad: Quad) {
+        if quad.size_x <= 0 || quad.size_y <= 0 || quad.color >> 24 == 0 {
+            return;
+        }
+
+        let texture = self.textures.get(quad.texture_index as usize);
+        let screen = &mut self.screen;
+
+        let x_min = quad.dest_x.max(0);
+        let x_max = (quad.dest_x + quad.size_x).min(screen.size_x);
+        let y_min = quad.dest_y.max(0);
+        let y_max = (quad.dest_y + quad.size_y).min(screen.size_y);
+
+        for y in y_min..y_max {
+            // Sample at pixel centers; rows count down from the quad's top edge
+            let j = quad.dest_y + quad.size_y - 1 - y;
+            let src_y = quad.src_y + ((2 * j + 1) * quad.src_size_y) / (2 * quad.size_y);
+            let row = ((screen.size_y - 1 - y) * screen.size_x) as usize;
+
+            for x in x_min..x_max {
+                let i = x - quad.dest_x;
+                let src_x = quad.src_x + ((2 * i + 1) * quad.src_size_x) / (2 * quad.size_x);
+
+                let texel = match texture {
+                    Some(texture) if src_x >= 0 && src_x < texture.size_x && src_y >= 0 && src_y < texture.size_y =>
+                        texture.pixels[(src_y * texture.size_x + src_x) as usize],
+                    Some(_) => 0,
+                    None => 0xffffffff,
+                };
+
+                let pixel = &mut screen.pixels[row + x as usize];
+                *pixel = blend(*pixel, modulate(texel, quad.color));
+            }
+        }
+    }
+
+    fn draw_map_layer(&mut self, quads: &[Quad], _dirty: &[u32], _rebuild: bool, offset: Coord, scale: i32) {
+        for quad in quads {
+            self.draw_quad(Quad {
+                dest_x: offset.0 + quad.dest_x * scale,
+                dest_y: offset.1 + quad.dest_y * scale,
+                size_x: quad.size_x * scale,
+                size_y: quad.size_y * scale,
+                ..*quad
+            });
+        }
+    }
+}
+
+// Multiply two colors channel by channel
+
+fn modulate(a: u32, b: u32) -> u32 {
+    let channel = |shift: u32| -> u32 {
+        let product = ((a >> shift) & 0xff) * ((b >> shift) & 0xff);
+        ((product + 127) / 255) << shift
+    };
+    channel(24) | channel(16) | channel(8) | channel(0)
+}
+
+/// Draw src over an opaque dest color
+pub fn blend(dest: u32, src: u32) -> u32 {
+    let alpha = src >> 24;
+    let channel = |shift: u32| -> u32 {
+        let s = (src >> shift) & 0xff;
+        let d = (dest >> shift) & 0xff;
+        ((s * alpha + d * (255 - alpha) + 127) / 255) << shift
+    };
+    OPAQUE_BLACK | channel(16) | channel(8) | channel(0)
+}
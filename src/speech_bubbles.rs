@@ -1,7 +1,9 @@
+use crate::animation::fade_color;
 use crate::color_preset;
 use crate::coord::Coord;
-use crate::fontdata::{Glyph, GLYPH, LINE_HEIGHT};
-use crate::engine;
+use crate::game::draw_scaled_tile_by_index;
+use crate::render::Renderer;
+use crate::text_layout::{Align, TextLayout, draw_text_layout, layout_text, line_height};
 
 use std::cmp::{min, max};
 
@@ -13,6 +15,7 @@ pub enum PopupType {
     Narration,
 }
 
+#[derive(Clone)]
 pub struct Popup {
     pub popup_type: PopupType,
     pub world_origin: Coord, // world tile position
@@ -22,12 +25,22 @@ pub struct Popup {
 struct PopupPlaced {
     pub popup_type: PopupType,
     pub world_origin: Coord, // world tile position
-    pub msg: &'static str,
+    pub text: TextLayout,
     pub size: Coord, // 16x16 tiles
     pub offset: Coord, // pixels, from top left to first character position
     pub pos: Coord,
 }
 
+// How the world maps onto the screen when drawing popups
+
+pub struct PopupView {
+    pub screen_size: Coord,
+    pub scale: Coord, // pixels per world tile
+    pub offset: Coord, // screen position of world tile (0, 0)
+    pub focus: Coord, // world tile position popups are placed away from, usually the player
+}
+
+#[derive(Clone)]
 pub struct Popups {
     popups: Vec<Popup>
 }
@@ -42,6 +55,18 @@ impl Popups {
     pub fn clear(&mut self) {
         self.popups.clear();
     }
+
+    pub fn clear_narration(&mut self) {
+        self.popups.retain(|popup| popup.popup_type != PopupType::Narration);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.popups.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Popup> {
+        self.popups.iter()
+    }
     
     pub fn guard_speech(&mut self, pos: Coord, s: &'static str) {
         self.push(PopupType::GuardSpeech, pos, s);
@@ -55,13 +80,21 @@ impl Popups {
         self.push(PopupType::Noise, pos, s);
     }
 
-    pub fn draw(&self, screen_size_x: i32, screen_size_y: i32, view_scale: Coord, view_offset: Coord, focus: Coord) {
-        // Compute the correct world area that is visible in the viewport?
-        let view_min = Coord(0, 0);
-        let view_max = Coord(screen_size_x, screen_size_y);
-        let placed_popups = layout(view_min, view_max, focus, &self.popups);
+    pub fn narration(&mut self, pos: Coord, s: &'static str) {
+        self.push(PopupType::Narration, pos, s);
+    }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer, view: &PopupView, opacity: f32) {
+        // World area that is visible in the viewport
+        let world_from_screen = |pos: Coord| {
+            let pos = pos - view.offset;
+            Coord(pos.0.div_euclid(view.scale.0), pos.1.div_euclid(view.scale.1))
+        };
+        let view_min = world_from_screen(Coord(0, 0));
+        let view_max = world_from_screen(view.screen_size);
+        let placed_popups = layout(view_min, view_max, view.focus, view.scale.0, &self.popups);
         for p in &placed_popups {
-            draw_popup(view_scale, view_offset, p);
+            draw_popup(renderer, view.scale, view.offset, p, opacity);
         }
     }
 
@@ -103,6 +136,15 @@ fn border_color(popup_type: PopupType) -> u32 {
     }
 }
 
+// Pixel width at which popup text wraps
+
+fn text_width_max(popup_type: PopupType) -> i32 {
+    match popup_type {
+        PopupType::Narration => 480,
+        _ => 240,
+    }
+}
+
 fn has_border(popup_type: PopupType) -> bool {
     match popup_type {
         PopupType::Noise => false,
@@ -112,22 +154,12 @@ fn has_border(popup_type: PopupType) -> bool {
     }
 }
 
-// This function and TILE_SIZE are duplicated from game.rs!
-
-fn draw_tile_by_index(tile_index: u32, dest_x: i32, dest_y: i32, color: u32) {
-    const TILE_SIZE: i32 = 16;
-    const TEXTURE_INDEX: u32 = 0;
-    let src_x = ((tile_index & 15) * 16) as i32;
-    let src_y = (240 - (tile_index & !15)) as i32;
-    engine::draw_tile(dest_x, dest_y, TILE_SIZE, TILE_SIZE, color, TEXTURE_INDEX, src_x, src_y);
-}
-
-fn draw_popup(view_scale: Coord, view_offset: Coord, p: &PopupPlaced) {
+fn draw_popup(renderer: &mut dyn Renderer, view_scale: Coord, view_offset: Coord, p: &PopupPlaced, opacity: f32) {
     let has_box = has_border(p.popup_type);
 
     let screen_from_world = |pos: Coord| { view_offset + view_scale.mul_components(pos) };
 
-    let text_y_offset = -LINE_HEIGHT - p.offset.1 + if has_box {0} else {-16};
+    let text_y_offset = -line_height() - p.offset.1 + if has_box {0} else {-view_scale.1};
     let text_pos = screen_from_world(p.pos + Coord(0, p.size.1) + if has_box {Coord(1, -1)} else {Coord(0, 0)}) + Coord(0, text_y_offset);
 
     // Draw background and border.
@@ -135,21 +167,21 @@ fn draw_popup(view_scale: Coord, view_offset: Coord, p: &PopupPlaced) {
     let box_pos = screen_from_world(p.pos);
     let box_size = view_scale.mul_components(p.size);
 
-    let put_tile = |tile_index: u32, x: i32, y: i32, color: u32| {
+    let put_tile = |renderer: &mut dyn Renderer, tile_index: u32, x: i32, y: i32, color: u32| {
         let pos = screen_from_world(Coord(x, y));
-        draw_tile_by_index(tile_index, pos.0, pos.1, color);
+        draw_scaled_tile_by_index(renderer, tile_index, pos.0, pos.1, view_scale.0, color);
     };
 
-    let fill_rect = |tile_index: u32, min_x: i32, min_y: i32, size_x: i32, size_y: i32, color: u32| {
+    let fill_rect = |renderer: &mut dyn Renderer, tile_index: u32, min_x: i32, min_y: i32, size_x: i32, size_y: i32, color: u32| {
         for x in min_x..min_x+size_x {
             for y in min_y..min_y+size_y {
-                put_tile(tile_index, x, y, color);
+                put_tile(renderer, tile_index, x, y, color);
             }
         }
     };
 
     if has_box {
-        engine::draw_rect(box_pos.0, box_pos.1, box_size.0, box_size.1, background_color(p.popup_type));
+        renderer.draw_rect(box_pos.0, box_pos.1, box_size.0, box_size.1, fade_color(background_color(p.popup_type), opacity));
 
         let sx = p.size.0;
         let sy = p.size.1;
@@ -157,25 +189,29 @@ fn draw_popup(view_scale: Coord, view_offset: Coord, p: &PopupPlaced) {
         let y0 = p.pos.1;
         let x1 = p.pos.0 + sx - 1;
         let y1 = p.pos.1 + sy - 1;
-        let border_color = border_color(p.popup_type);
-
-        put_tile(228, x0, y0, border_color);
-        put_tile(229, x1, y0, border_color);
-        put_tile(230, x0, y1, border_color);
-        put_tile(231, x1, y1, border_color);
-        fill_rect(226, x0 + 1, y0, sx-2, 1, border_color);
-        fill_rect(227, x0 + 1, y1, sx-2, 1, border_color);
-        fill_rect(224, x0, y0 + 1, 1, sy-2, border_color);
-        fill_rect(225, x1, y0 + 1, 1, sy-2, border_color);
-    } else {
+        let border_color = fade_color(border_color(p.popup_type), opacity);
+
+        put_tile(renderer, 228, x0, y0, border_color);
+        put_tile(renderer, 229, x1, y0, border_color);
+        put_tile(renderer, 230, x0, y1, border_color);
+        put_tile(renderer, 231, x1, y1, border_color);
+        fill_rect(renderer, 226, x0 + 1, y0, sx-2, 1, border_color);
+        fill_rect(renderer, 227, x0 + 1, y1, sx-2, 1, border_color);
+        fill_rect(renderer, 224, x0, y0 + 1, 1, sy-2, border_color);
+        fill_rect(renderer, 225, x1, y0 + 1, 1, sy-2, border_color);
+    }
+
+    let text_width = view_scale.0 * (p.size.0 - if has_box {2} else {0});
+
+    if !has_box {
         // Draw text "outline"
-        puts_proportional(text_pos.0 + 2, text_pos.1 - 2, p.msg, color_preset::BLACK);
-        puts_proportional(text_pos.0 + 1, text_pos.1 - 1, p.msg, color_preset::BLACK);
+        draw_text_layout(renderer, &p.text, text_pos.0 + 2, text_pos.1 - 2, text_width, Align::Center, fade_color(color_preset::BLACK, opacity));
+        draw_text_layout(renderer, &p.text, text_pos.0 + 1, text_pos.1 - 1, text_width, Align::Center, fade_color(color_preset::BLACK, opacity));
     }
 
     // Draw the text
 
-    puts_proportional(text_pos.0, text_pos.1, p.msg, text_color(p.popup_type));
+    draw_text_layout(renderer, &p.text, text_pos.0, text_pos.1, text_width, Align::Center, fade_color(text_color(p.popup_type), opacity));
 }
 
 type Score = (i32, f32);
@@ -205,45 +241,23 @@ fn compute_score(
     (offscreen_area, -dot)
 }
 
-fn size_and_offset(p: &Popup) -> (Coord, Coord) {
-
-    // Very similar to get_horizontal_extents:
-
-    let mut num_lines = 1;
-    let mut x_min = std::i32::MAX;
-    let mut x_max = std::i32::MIN;
-    let mut x = 0;
-
-    for c in p.msg.chars() {
-        if c == '\n' {
-            num_lines += 1;
-            x = 0;
-        }
-        else if let Some(glyph) = glyph_lookup(c) {
-            x_min = min(x_min, x + glyph.x_offset);
-            x_max = max(x_max, x + glyph.x_offset + glyph.width);
-            x += glyph.x_advance;
-        }
-    }
-
-    let width = x_max - x_min;
-    let height = num_lines * LINE_HEIGHT;
-
-    const TILE_SCREEN_SIZE: i32 = 16;// * g_worldScale; // This needs to get plumbed in; it allows rendering at different sizes
+fn size_and_offset(text: &TextLayout, popup_type: PopupType, tile_screen_size: i32) -> (Coord, Coord) {
+    let width = text.width;
+    let height = text.height;
 
     let size_internal = Coord(
-        (width + TILE_SCREEN_SIZE - 1) / TILE_SCREEN_SIZE,
-        (height + TILE_SCREEN_SIZE - 1) / TILE_SCREEN_SIZE
+        (width + tile_screen_size - 1) / tile_screen_size,
+        (height + tile_screen_size - 1) / tile_screen_size
     );
 
     let offset = Coord(
-        (TILE_SCREEN_SIZE * size_internal.0 - width) / 2 - x_min,
-        (TILE_SCREEN_SIZE * size_internal.1 - height) / 2
+        (tile_screen_size * size_internal.0 - width) / 2,
+        (tile_screen_size * size_internal.1 - height) / 2
     );
 
     // Non-noise text boxes have borders.
 
-    let size = if has_border(p.popup_type) {
+    let size = if has_border(popup_type) {
         size_internal + Coord(2, 2)
     } else {
         size_internal
@@ -252,16 +266,18 @@ fn size_and_offset(p: &Popup) -> (Coord, Coord) {
     (size, offset)
 }
 
-fn layout_single(view_min: Coord, view_max: Coord, focus: Coord, p: &Popup) -> PopupPlaced {
+fn layout_single(view_min: Coord, view_max: Coord, focus: Coord, tile_screen_size: i32, p: &Popup) -> PopupPlaced {
 
-    let (size, offset) = size_and_offset(p);
+    let text = layout_text(p.msg, text_width_max(p.popup_type));
+    let (size, offset) = size_and_offset(&text, p.popup_type, tile_screen_size);
 
     let mut pos = Coord(0, 0);
 
     if p.popup_type == PopupType::Narration {
-        // Center narration.
+        // Center narration horizontally, in the upper half of the view so it
+        // doesn't cover the player.
         pos.0 = (view_min.0 + view_max.0 - size.0) / 2;
-        pos.1 = (view_min.1 + view_max.1 - size.1) / 2;
+        pos.1 = (view_min.1 + 3 * view_max.1) / 4 - size.1 / 2;
     } else {
         // Search for a position that is on the opposite side of the source
         // from the focus, with a center position as close as possible to
@@ -317,59 +333,13 @@ fn layout_single(view_min: Coord, view_max: Coord, focus: Coord, p: &Popup) -> P
     PopupPlaced {
         popup_type: p.popup_type,
         world_origin: p.world_origin,
-        msg: p.msg,
+        text,
         size: size,
         offset: offset,
         pos: pos,
     }
 }
 
-fn layout(view_min: Coord, view_max: Coord, focus: Coord, popups: &[Popup]) -> Vec<PopupPlaced> {
-    popups.iter().map(|p| layout_single(view_min, view_max, focus, &p)).collect()
-}
-
-pub fn glyph_lookup(c: char) -> Option<&'static Glyph> {
-    let id = c as usize;
-    GLYPH.iter().find(|&glyph| glyph.id == id)
-}
-
-pub fn get_horizontal_extents(s: &str) -> (i32, i32) {
-    let mut x_min = std::i32::MAX;
-    let mut x_max = std::i32::MIN;
-    let mut x = 0;
-
-    for c in s.chars() {
-        if let Some(glyph) = glyph_lookup(c) {
-            x_min = min(x_min, x + glyph.x_offset);
-            x_max = max(x_max, x + glyph.x_offset + glyph.width);
-            x += glyph.x_advance;
-        }
-    }
-
-    (x_min, x_max)
-}
-
-pub fn puts_proportional(mut x: i32, mut y: i32, s: &str, color: u32) -> i32 {
-    let x_base = x;
-    const TEXTURE_INDEX: u32 = 1;
-
-    for c in s.chars() {
-        if c == '\n' {
-            y -= if x == x_base {LINE_HEIGHT / 2} else {LINE_HEIGHT};
-            x = x_base;
-        } else if let Some(glyph) = glyph_lookup(c) {
-            engine::draw_tile(
-                x + glyph.x_offset,
-                y + LINE_HEIGHT - (glyph.height + glyph.y_offset),
-                glyph.width,
-                glyph.height,
-                color,
-                TEXTURE_INDEX,
-                glyph.x,
-                glyph.y);
-            x += glyph.x_advance;
-        }
-    }
-
-    x
+fn layout(view_min: Coord, view_max: Coord, focus: Coord, tile_screen_size: i32, popups: &[Popup]) -> Vec<PopupPlaced> {
+    popups.iter().map(|p| layout_single(view_min, view_max, focus, tile_screen_size, &p)).collect()
 }
@@ -0,0 +1,296 @@
+use crate::coord::Coord;
+use crate::game::{self, Game};
+use crate::render::{OPAQUE_BLACK, Quad, Renderer, blend};
+use crate::text_layout::line_height;
+use crate::tiles;
+
+use std::ops::Range;
+
+// Running the game in a text terminal. TerminalRenderer draws into a grid of
+// character cells rather than pixels: the game draws a screen of CELL_SIZE_X by
+// CELL_SIZE_Y pixels per cell, as usual, and each quad is turned into
+// characters. Tiles become pairs of Unicode characters, since a tile is two
+// cells wide (which is close to square in most terminals); text is shown as its
+// own characters; and solid rectangles become background colors. The
+// disguiser-terminal binary (src/bin/terminal.rs) shows the cells with ANSI
+// escape codes.
+
+pub const CELL_SIZE_X: i32 = 8;
+pub const CELL_SIZE_Y: i32 = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct TerminalCell {
+    pub c: char,
+    pub fg: u32, // colors are opaque, in the usual 0xAABBGGRR form
+    pub bg: u32,
+}
+
+const BLANK_CELL: TerminalCell = TerminalCell { c: ' ', fg: OPAQUE_BLACK, bg: OPAQUE_BLACK };
+
+pub struct TerminalRenderer {
+    pub size_x: i32, // in cells
+    pub size_y: i32,
+    pub cells: Vec<TerminalCell>, // rows from the top down
+    text_cursor: Option<TextCursor>,
+}
+
+// Where the last character of text went, so that the next one on the same line
+// can go in the cell after it. Proportional text doesn't line up with cells.
+// Kerning can move a glyph left of the one before, but not by a whole cell.
+
+struct TextCursor {
+    line_start: Coord,
+    dest_x: i32,
+    cell: Coord,
+}
+
+// Texture indices, as loaded by game.js
+
+const TEXTURE_INDEX_TILES: u32 = 0;
+const TEXTURE_INDEX_FONT: u32 = 1;
+
+pub fn new_terminal_renderer(size_x: i32, size_y: i32) -> TerminalRenderer {
+    TerminalRenderer {
+        size_x,
+        size_y,
+        cells: vec![BLANK_CELL; (size_x * size_y) as usize],
+        text_cursor: None,
+    }
+}
+
+impl TerminalRenderer {
+    pub fn clear(&mut self) {
+        self.cells.fill(BLANK_CELL);
+        self.text_cursor = None;
+    }
+
+    /// Size of the screen the game draws, in pixels
+    pub fn screen_size(&self) -> Coord {
+        Coord(self.size_x * CELL_SIZE_X, self.size_y * CELL_SIZE_Y)
+    }
+
+    pub fn cell(&self, x: i32, y: i32) -> &TerminalCell {
+        &self.cells[(y * self.size_x + x) as usize]
+    }
+
+    // Cell containing a screen position, which has y pointing up
+
+    fn cell_at(&self, pos: Coord) -> Option<Coord> {
+        let x = pos.0.div_euclid(CELL_SIZE_X);
+        let y = self.size_y - 1 - pos.1.div_euclid(CELL_SIZE_Y);
+        if x >= 0 && x < self.size_x && y >= 0 && y < self.size_y {
+            Some(Coord(x, y))
+        } else {
+            None
+        }
+    }
+
+    fn cell_mut(&mut self, cell: Coord) -> &mut TerminalCell {
+        &mut self.cells[(cell.1 * self.size_x + cell.0) as usize]
+    }
+
+    fn put_char(&mut self, cell: Coord, c: char, color: u32) {
+        let cell = self.cell_mut(cell);
+        cell.c = c;
+        cell.fg = blend(cell.fg, color);
+    }
+
+    // Solid rectangles color the cells whose centers they cover, text included.
+
+    fn fill(&mut self, quad: &Quad) {
+        let opaque = quad.color >> 24 == 0xff;
+        let rows = cells_covered(self.size_y * CELL_SIZE_Y - (quad.dest_y + quad.size_y), quad.size_y, CELL_SIZE_Y, self.size_y);
+        for y in rows {
+            for x in cells_covered(quad.dest_x, quad.size_x, CELL_SIZE_X, self.size_x) {
+                let cell = self.cell_mut(Coord(x, y));
+                cell.bg = blend(cell.bg, quad.color);
+                cell.fg = blend(cell.fg, quad.color);
+                if opaque {
+                    cell.c = ' ';
+                }
+            }
+        }
+    }
+
+    // A tile's pair of characters goes in the two cells across its middle.
+    // Spaces are transparent, as the tiles' empty pixels are.
+
+    fn put_tile(&mut self, quad: &Quad) {
+        let tile_index = match game::quad_tile_index(quad) {
+            Some(tile_index) => tile_index,
+            None => return,
+        };
+        let center = Coord(quad.dest_x + quad.size_x / 2, quad.dest_y + quad.size_y / 2);
+
+        for (c, dx) in tile_chars(tile_index).iter().zip(&[-CELL_SIZE_X / 2, CELL_SIZE_X / 2]) {
+            if *c == ' ' {
+                continue;
+            }
+            if let Some(cell) = self.cell_at(center + Coord(*dx, 0)) {
+                self.put_char(cell, *c, quad.color);
+            }
+        }
+    }
+}
+
+// Range of cells, along one axis, whose centers are within a span of pixels
+
+fn cells_covered(min: i32, size: i32, cell_size: i32, cell_count: i32) -> Range<i32> {
+    let first = (min - cell_size / 2 + cell_size - 1).div_euclid(cell_size);
+    let end = (min + size - cell_size / 2 + cell_size - 1).div_euclid(cell_size);
+    first.max(0)..end.min(cell_count)
+}
+
+impl Renderer for TerminalRenderer {
+    fn draw_quad(&mut self, quad: Quad) {
+        self.text_cursor = None;
+
+        if quad.size_x <= 0 || quad.size_y <= 0 || quad.color >> 24 == 0 {
+            return;
+        }
+
+        match quad.texture_index {
+            TEXTURE_INDEX_TILES => self.put_tile(&quad),
+            TEXTURE_INDEX_FONT => {}, // glyphs are drawn by draw_char
+            _ => self.fill(&quad),
+        }
+    }
+
+    fn draw_map_layer(&mut self, quads: &[Quad], _dirty: &[u32], _rebuild: bool, offset: Coord, scale: i32) {
+        for quad in quads {
+            self.draw_quad(Quad {
+                dest_x: offset.0 + quad.dest_x * scale,
+                dest_y: offset.1 + quad.dest_y * scale,
+                size_x: quad.size_x * scale,
+                size_y: quad.size_y * scale,
+                ..*quad
+            });
+        }
+    }
+
+    // Each character of a line goes in the cell after the one before, starting
+    // from the cell under the first. The line sits in the row through its middle.
+
+    fn draw_char(&mut self, c: char, line_start: Coord, quad: Quad) {
+        let cell = match &self.text_cursor {
+            Some(cursor) if cursor.line_start == line_start && quad.dest_x > cursor.dest_x - CELL_SIZE_X => Some(cursor.cell + Coord(1, 0)),
+            _ => self.cell_at(Coord(quad.dest_x, line_start.1 + line_height() / 2)),
+        };
+
+        let cell = match cell {
+            Some(cell) => cell,
+            None => {
+                self.text_cursor = None;
+                return;
+            },
+        };
+
+        self.text_cursor = Some(TextCursor { line_start, dest_x: quad.dest_x, cell });
+
+        if c != ' ' && cell.0 < self.size_x && quad.color >> 24 != 0 {
+            self.put_char(cell, c, quad.color);
+        }
+    }
+}
+
+// Characters for the left and right halves of each tile in tiles.png. Map
+// cells and items take theirs from tiles.txt; the rest are for text, actors and
+// the interface.
+
+fn tile_chars(tile_index: u32) -> [char; 2] {
+    if let Some(chars) = tiles::terminal_chars(tile_index) {
+        return chars;
+    }
+
+    match tile_index {
+        4 => ['◆', ' '],
+        5 => ['♥', ' '],
+        6 => ['♠', ' '],
+        7 => ['·', ' '],
+        9 => ['•', ' '],
+        31 => ['✶', ' '],
+        32..=126 => [char::from(tile_index as u8), ' '],
+
+        // Player, guards, and the icons over them
+        174 => ['«', ' '],
+        175 => ['»', ' '],
+        208..=211 => ['@', ' '],
+        212..=215 => ['G', ' '],
+        216 => ['?', ' '],
+        217 => ['!', ' '],
+        218 => ['▼', ' '],
+        219 => ['•', ' '],
+        220 => ['○', ' '],
+
+        // Speech bubble borders
+        224 => ['│', ' '],
+        225 => [' ', '│'],
+        226 | 227 => ['─', '─'],
+        228 => ['╰', '─'],
+        229 => ['─', '╯'],
+        230 => ['╭', '─'],
+        231 => ['─', '╮'],
+
+        254 => ['■', ' '],
+        255 => ['█', '█'],
+        _ => ['?', ' '],
+    }
+}
+
+// The game, drawn to a TerminalRenderer. Unlike Headless, animations play in
+// real time, as the frontend calls tick().
+
+pub struct Terminal {
+    game: Game,
+    pub renderer: TerminalRenderer,
+}
+
+pub fn new_terminal(seed: u64, size_x: i32, size_y: i32) -> Terminal {
+    let mut terminal = Terminal {
+        game: game::new_game(seed),
+        renderer: new_terminal_renderer(size_x, size_y),
+    };
+    terminal.resize(size_x, size_y);
+    terminal
+}
+
+impl Terminal {
+    /// Change the size of the terminal, in cells
+    pub fn resize(&mut self, size_x: i32, size_y: i32) {
+        self.renderer = new_terminal_renderer(size_x, size_y);
+        let screen_size = self.renderer.screen_size();
+        game::on_resize(&mut self.game, screen_size.0, screen_size.1);
+    }
+
+    pub fn key_down(&mut self, key: i32, ctrl_key_down: bool, shift_key_down: bool) {
+        game::on_key_down(&mut self.game, key, ctrl_key_down, shift_key_down);
+        game::play_queued_sounds(&mut self.game);
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        game::on_tick(&mut self.game, dt_ms);
+    }
+
+    pub fn draw(&mut self) -> &TerminalRenderer {
+        let screen_size = self.renderer.screen_size();
+        self.renderer.clear();
+        game::on_draw(&mut self.game, &mut self.renderer, screen_size.0, screen_size.1);
+        &self.renderer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_covered_by_spans() {
+        // Cells are 8 pixels wide, with centers at 4, 12, 20, ...
+        assert_eq!(cells_covered(0, 16, 8, 10), 0..2);
+        assert_eq!(cells_covered(4, 8, 8, 10), 0..1);
+        assert_eq!(cells_covered(5, 8, 8, 10), 1..2);
+        assert_eq!(cells_covered(5, 2, 8, 10), 1..1);
+        assert_eq!(cells_covered(-16, 32, 8, 10), 0..2);
+        assert_eq!(cells_covered(61, 40, 8, 10), 8..10);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::coord::Coord;
+use crate::font::{Font, with_font};
+use crate::fontdata::Glyph;
+use crate::render::{Quad, Renderer};
+
+use std::cmp::{min, max};
+
+// Proportional text: measuring, wrapping to a pixel width, and drawing.
+//
+// Text is drawn downward from its first line. Each line occupies the font's line
+// height, except blank lines, which take half that so paragraphs can be
+// separated without leaving big gaps.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+pub struct TextLayout {
+    pub lines: Vec<String>,
+    pub width: i32, // widest line's inked extent
+    pub height: i32,
+}
+
+// The font covers ASCII and the accented Latin alphabets. Typographic punctuation
+// outside it is drawn as its ASCII look-alike, and anything else as a question mark.
+
+fn glyph_lookup(font: &Font, c: char) -> Option<&Glyph> {
+    if c.is_control() {
+        return None;
+    }
+
+    font.glyph(c).or_else(|| {
+        let substitute = match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201c}' | '\u{201d}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            _ => '?',
+        };
+        font.glyph(substitute)
+    })
+}
+
+pub fn line_height() -> i32 {
+    with_font(|font| font.line_height)
+}
+
+// Leftmost and rightmost inked pixels of a single line, relative to its start
+
+pub fn get_horizontal_extents(s: &str) -> (i32, i32) {
+    with_font(|font| {
+        let mut x_min = std::i32::MAX;
+        let mut x_max = std::i32::MIN;
+        let mut x = 0;
+        let mut c_prev = None;
+
+        for c in s.chars() {
+            if let Some(glyph) = glyph_lookup(font, c) {
+                x += c_prev.map_or(0, |c_prev| font.kerning(c_prev, c));
+                x_min = min(x_min, x + glyph.x_offset);
+                x_max = max(x_max, x + glyph.x_offset + glyph.width);
+                x += glyph.x_advance;
+                c_prev = Some(c);
+            }
+        }
+
+        // Lines with nothing inked, such as empty ones, have no width
+        if x_min > x_max {
+            return (0, 0);
+        }
+
+        (x_min, x_max)
+    })
+}
+
+pub fn line_width(s: &str) -> i32 {
+    let (x_min, x_max) = get_horizontal_extents(s);
+    max(0, x_max - x_min)
+}
+
+fn line_advance(line: &str) -> i32 {
+    if line.is_empty() {line_height() / 2} else {line_height()}
+}
+
+// Break text into lines no wider than width_max. Explicit newlines always break;
+// otherwise lines break between words, or inside a word too long to fit on its own.
+
+pub fn layout_text(s: &str, width_max: i32) -> TextLayout {
+    let mut lines = Vec::new();
+
+    for paragraph in s.split('\n') {
+        let mut line = String::new();
+
+        for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+            let candidate = if line.is_empty() {String::from(word)} else {format!("{} {}", line, word)};
+            if line_width(&candidate) <= width_max {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(line);
+            }
+
+            line = String::new();
+            for c in word.chars() {
+                line.push(c);
+                if line.chars().count() > 1 && line_width(&line) > width_max {
+                    line.pop();
+                    lines.push(line);
+                    line = c.to_string();
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    let width = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+    let height = lines.iter().map(|line| line_advance(line)).sum();
+
+    TextLayout { lines, width, height }
+}
+
+// Draw laid-out text, aligned within the span [x, x + width). y is the bottom
+// of the first line, as with puts_proportional. Returns where a following line would go.
+
+pub fn draw_text_layout(renderer: &mut dyn Renderer, text: &TextLayout, x: i32, mut y: i32, width: i32, align: Align, color: u32) -> i32 {
+    for line in &text.lines {
+        if !line.is_empty() {
+            let (x_min, x_max) = get_horizontal_extents(line);
+            let x_line = match align {
+                Align::Left => x,
+                Align::Center => x + (width - (x_max - x_min)) / 2 - x_min,
+                Align::Right => x + width - x_max,
+            };
+
+            puts_proportional(renderer, x_line, y, line, color);
+        }
+
+        y -= line_advance(line);
+    }
+
+    y
+}
+
+pub fn puts_proportional(renderer: &mut dyn Renderer, mut x: i32, mut y: i32, s: &str, color: u32) -> i32 {
+    let x_base = x;
+    const TEXTURE_INDEX: u32 = 1;
+
+    with_font(|font| {
+        let mut c_prev = None;
+
+        for c in s.chars() {
+            if c == '\n' {
+                y -= if x == x_base {font.line_height / 2} else {font.line_height};
+                x = x_base;
+                c_prev = None;
+            } else if let Some(glyph) = glyph_lookup(font, c) {
+                x += c_prev.map_or(0, |c_prev| font.kerning(c_prev, c));
+                renderer.draw_char(c, Coord(x_base, y), Quad {
+                    dest_x: x + glyph.x_offset,
+                    dest_y: y + font.line_height - (glyph.height + glyph.y_offset),
+                    size_x: glyph.width,
+                    size_y: glyph.height,
+                    color,
+                    texture_index: TEXTURE_INDEX,
+                    src_x: glyph.x,
+                    src_y: glyph.y,
+                    src_size_x: glyph.width,
+                    src_size_y: glyph.height,
+                });
+                x += glyph.x_advance;
+                c_prev = Some(c);
+            }
+        }
+
+        x
+    })
+}
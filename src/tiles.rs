@@ -0,0 +1,233 @@
+use crate::audio::Sound;
+use crate::cell_grid::{CELL_TYPES, CellType, INFINITE_COST, ITEM_KINDS, ItemKind};
+use crate::color_preset;
+
+use std::sync::OnceLock;
+
+// Definitions of how each cell type and item kind looks and behaves. These are
+// read from tiles.txt, which is compiled in, the first time they're needed.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    pub glyph: u32,
+    pub terminal_chars: [char; 2], // left and right halves; spaces are blank
+    pub color: u32,
+    pub guard_move_cost: usize,
+    pub noise: Option<Noise>,
+    pub footstep_sound: Sound,
+    pub blocks_player: bool,
+    pub blocks_player_sight: bool,
+    pub blocks_sight: bool,
+    pub blocks_sound: bool,
+    pub ignores_lighting: bool,
+    pub hides_player: bool,
+}
+
+// Sounds the player makes when moving onto a tile
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Noise {
+    Creak,
+}
+
+struct Registry {
+    cells: Vec<Tile>, // indexed by CellType
+    items: Vec<Tile>, // parallel to ITEM_KINDS
+}
+
+const TILE_DEFINITIONS: &str = include_str!("tiles.txt");
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| parse_registry(TILE_DEFINITIONS).unwrap_or_else(|error| panic!("tiles.txt: {}", error)))
+}
+
+pub fn tile_def(cell_type: CellType) -> &'static Tile {
+    &registry().cells[cell_type as usize]
+}
+
+pub fn item_def(kind: ItemKind) -> &'static Tile {
+    let kind = if let ItemKind::Outfit(_) = kind {ItemKind::Outfit(None)} else {kind};
+    let i = ITEM_KINDS.iter().position(|&item_kind| item_kind == kind).unwrap();
+    &registry().items[i]
+}
+
+// Characters for a glyph, if some cell type or item kind is drawn with it
+
+pub fn terminal_chars(glyph: u32) -> Option<[char; 2]> {
+    let registry = registry();
+    registry.cells.iter().chain(&registry.items).find(|tile| tile.glyph == glyph).map(|tile| tile.terminal_chars)
+}
+
+fn parse_registry(definitions: &str) -> Result<Registry, String> {
+    let mut cells: Vec<Option<Tile>> = vec![None; CELL_TYPES.len()];
+    let mut items: Vec<Option<Tile>> = vec![None; ITEM_KINDS.len()];
+
+    for (line_index, line) in definitions.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            return Err(format!("line {}: expected at least 8 fields", line_number));
+        }
+
+        let name = fields[1];
+        let tile = parse_tile(&fields[2..]).map_err(|error| format!("line {}: {}", line_number, error))?;
+
+        if cells.iter().chain(&items).flatten().any(|other| other.glyph == tile.glyph && other.terminal_chars != tile.terminal_chars) {
+            return Err(format!("line {}: glyph {} has different characters elsewhere", line_number, tile.glyph));
+        }
+
+        let slot = match fields[0] {
+            "cell" => CELL_TYPES.iter().position(|cell_type| format!("{:?}", cell_type) == name).map(|i| &mut cells[i]),
+            "item" => ITEM_KINDS.iter().position(|kind| item_kind_name(*kind) == name).map(|i| &mut items[i]),
+            kind => return Err(format!("line {}: unknown definition kind {}", line_number, kind)),
+        };
+
+        match slot {
+            Some(slot) if slot.is_some() => return Err(format!("line {}: {} is defined more than once", line_number, name)),
+            Some(slot) => *slot = Some(tile),
+            None => return Err(format!("line {}: unknown {} {}", line_number, fields[0], name)),
+        }
+    }
+
+    if let Some(i) = cells.iter().position(|tile| tile.is_none()) {
+        return Err(format!("no definition for cell {:?}", CELL_TYPES[i]));
+    }
+
+    if let Some(i) = items.iter().position(|tile| tile.is_none()) {
+        return Err(format!("no definition for item {}", item_kind_name(ITEM_KINDS[i])));
+    }
+
+    Ok(Registry {
+        cells: cells.into_iter().flatten().collect(),
+        items: items.into_iter().flatten().collect(),
+    })
+}
+
+// Parse the fields following the name: glyph, chars, color, cost, noise, sound, and flags
+
+fn parse_tile(fields: &[&str]) -> Result<Tile, String> {
+    let glyph = fields[0].parse().map_err(|_| format!("bad glyph {}", fields[0]))?;
+
+    let terminal_chars: Vec<char> = fields[1].chars().map(|c| if c == '_' {' '} else {c}).collect();
+    let terminal_chars = match terminal_chars[..] {
+        [left, right] => [left, right],
+        _ => return Err(format!("bad characters {}", fields[1])),
+    };
+
+    let color = if let Some(hex) = fields[2].strip_prefix("0x") {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        color_preset::from_name(fields[2])
+    };
+    let color = color.ok_or_else(|| format!("bad color {}", fields[2]))?;
+
+    let guard_move_cost = match fields[3] {
+        "inf" => INFINITE_COST,
+        cost => cost.parse().map_err(|_| format!("bad guard move cost {}", cost))?,
+    };
+
+    let noise = match fields[4] {
+        "-" => None,
+        "creak" => Some(Noise::Creak),
+        noise => return Err(format!("unknown noise {}", noise)),
+    };
+
+    let footstep_sound = match fields[5] {
+        "-" => Sound::Footstep,
+        "creak" => Sound::Creak,
+        "splash" => Sound::Splash,
+        sound => return Err(format!("unknown sound {}", sound)),
+    };
+
+    let mut tile = Tile {
+        glyph,
+        terminal_chars,
+        color,
+        guard_move_cost,
+        noise,
+        footstep_sound,
+        blocks_player: false,
+        blocks_player_sight: false,
+        blocks_sight: false,
+        blocks_sound: false,
+        ignores_lighting: false,
+        hides_player: false,
+    };
+
+    for &flag in &fields[6..] {
+        match flag {
+            "blocks_player" => tile.blocks_player = true,
+            "blocks_player_sight" => tile.blocks_player_sight = true,
+            "blocks_sight" => tile.blocks_sight = true,
+            "blocks_sound" => tile.blocks_sound = true,
+            "ignores_lighting" => tile.ignores_lighting = true,
+            "hides_player" => tile.hides_player = true,
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+
+    Ok(tile)
+}
+
+// Item kinds are named without their payload, so all outfits are "Outfit"
+
+fn item_kind_name(kind: ItemKind) -> String {
+    let name = format!("{:?}", kind);
+    match name.find('(') {
+        Some(i) => String::from(&name[..i]),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_definitions_parse() {
+        let registry = parse_registry(TILE_DEFINITIONS).unwrap_or_else(|error| panic!("tiles.txt: {}", error));
+        assert_eq!(registry.cells.len(), CELL_TYPES.len());
+        assert_eq!(registry.items.len(), ITEM_KINDS.len());
+
+        let water = tile_def(CellType::GroundWater);
+        assert_eq!(water.guard_move_cost, 4096);
+        assert_eq!(water.footstep_sound, Sound::Splash);
+
+        assert!(!tile_def(CellType::Wall0000).blocks_player_sight);
+
+        for &cell_type in &[CellType::OneWayWindowE, CellType::OneWayWindowW, CellType::OneWayWindowN, CellType::OneWayWindowS] {
+            let window = tile_def(cell_type);
+            assert!(window.blocks_sight);
+            assert!(!window.blocks_player);
+        }
+
+        let bush = item_def(ItemKind::Bush);
+        assert!(bush.blocks_sight);
+        assert!(bush.hides_player);
+
+        assert!(item_def(ItemKind::Table).hides_player);
+        assert!(item_def(ItemKind::DoorNS).blocks_player_sight);
+
+        assert_eq!(water.terminal_chars, ['≈', '≈']);
+        assert_eq!(terminal_chars(tile_def(CellType::DoorEW).glyph), Some([' ', '━']));
+    }
+
+    #[test]
+    fn glyphs_keep_their_characters() {
+        let error = parse_registry("cell GroundNormal 128 ·_ WHITE 0 - -\ncell GroundGrass 128 \"_ WHITE 0 - -\n").err().unwrap();
+        assert_eq!(error, "line 2: glyph 128 has different characters elsewhere");
+    }
+
+    #[test]
+    fn errors_report_line_numbers() {
+        let error = parse_registry("# comment\n\ncell Nowhere 0 __ WHITE 0 - - blocks_player\n").err().unwrap();
+        assert_eq!(error, "line 3: unknown cell Nowhere");
+    }
+}
@@ -0,0 +1,105 @@
+// Golden-image tests: each case starts a game with a fixed seed, plays a fixed
+// sequence of keys, draws a frame with the software renderer, and compares it
+// with tests/golden/<case>.png pixel for pixel.
+//
+// After an intended change to how things look, regenerate the images with
+//
+//     UPDATE_GOLDEN=1 cargo test --test golden
+//
+// and look over the new images before committing them. When a case fails, the
+// frame it drew is written to target/golden/<case>.png for comparison.
+
+extern crate disguiser;
+
+use disguiser::engine;
+use disguiser::headless::{load_png, load_textures, new_headless, save_png};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SCREEN_SIZE_X: i32 = 800;
+const SCREEN_SIZE_Y: i32 = 600;
+
+// A key press: key code, Ctrl held, Shift held
+
+type Key = (i32, bool, bool);
+
+const WAIT: Key = (engine::KEY_NUMPAD5, false, false);
+const HELP: Key = (engine::KEY_SLASH, false, false);
+const RIGHT: Key = (engine::KEY_RIGHT, false, false);
+const ZOOM_IN: Key = (engine::KEY_EQUAL, false, false);
+const MESSAGE_LOG: Key = (engine::KEY_P, false, false);
+const OPTIONS: Key = (engine::KEY_ESCAPE, false, false);
+const NEXT_LEVEL: Key = (engine::KEY_PERIOD, true, false);
+const SEE_ALL: Key = (engine::KEY_A, true, false);
+
+fn check(case: &str, seed: u64, keys: &[Key]) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let textures = load_textures(&root.join("web")).unwrap();
+
+    let mut headless = new_headless(seed, SCREEN_SIZE_X, SCREEN_SIZE_Y, textures);
+    for &(key, ctrl_key_down, shift_key_down) in keys {
+        headless.key_down(key, ctrl_key_down, shift_key_down);
+    }
+    let frame = headless.draw();
+
+    let golden_path = root.join("tests").join("golden").join(format!("{}.png", case));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        save_png(&golden_path, frame).unwrap();
+        return;
+    }
+
+    let golden = load_png(&golden_path).unwrap_or_else(|error| panic!("{} (run with UPDATE_GOLDEN=1 to create it)", error));
+
+    let pixels_different =
+        if golden.size_x != frame.size_x || golden.size_y != frame.size_y {
+            frame.pixels.len()
+        } else {
+            golden.pixels.iter().zip(&frame.pixels).filter(|(a, b)| a != b).count()
+        };
+
+    if pixels_different > 0 {
+        let failure_dir: PathBuf = root.join("target").join("golden");
+        fs::create_dir_all(&failure_dir).unwrap();
+        let failure_path = failure_dir.join(format!("{}.png", case));
+        save_png(&failure_path, frame).unwrap();
+        panic!("{}: {} pixels differ from {}; the frame drawn is in {}",
+            case, pixels_different, golden_path.display(), failure_path.display());
+    }
+}
+
+#[test]
+fn status_bars() {
+    check("status_bars", 3, &[]);
+}
+
+#[test]
+fn help_first_page() {
+    check("help_first_page", 3, &[HELP]);
+}
+
+#[test]
+fn help_second_page() {
+    check("help_second_page", 3, &[HELP, RIGHT]);
+}
+
+#[test]
+fn options_screen() {
+    check("options_screen", 3, &[OPTIONS]);
+}
+
+#[test]
+fn zoomed_in() {
+    check("zoomed_in", 3, &[ZOOM_IN]);
+}
+
+#[test]
+fn guard_speech_popup() {
+    check("guard_speech_popup", 4, &[NEXT_LEVEL, NEXT_LEVEL, SEE_ALL, WAIT, WAIT]);
+}
+
+#[test]
+fn message_log() {
+    check("message_log", 4, &[NEXT_LEVEL, NEXT_LEVEL, SEE_ALL, WAIT, WAIT, WAIT, MESSAGE_LOG]);
+}
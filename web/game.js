@@ -9,6 +9,11 @@ const vertexPositions = new Float32Array(maxQuads * 16);
 const vertexColors = new Uint32Array(maxQuads * 4);
 let numQuads = 0;
 
+// Quads passed from the game are ten 32-bit words: destination x, y, size x, y;
+// color; texture index; source x, y, size x, y. See engine::Quad.
+
+const quadWords = 10;
+
 // Projection matrix memory
 
 const projectionMatrix = new Float32Array(16);
@@ -23,17 +28,35 @@ const loadImage = src =>
 		img.src = src;
 	});
 
+// The font is described by a BMFont text descriptor, and drawn from the single page
+// image it names. If the descriptor can't be loaded, the font built into the game is
+// used, with its image.
+
+const fontDescriptorFile = 'font.fnt';
+const builtinFontImageFile = 'font.png';
+
+const loadFont = () =>
+	fetch(fontDescriptorFile)
+	.then(response => response.ok ? response.text() : Promise.reject(response.status))
+	.then(descriptor => {
+		const page = descriptor.match(/^page\s.*\bfile="([^"]*)"/m);
+		return page ?
+			loadImage(new URL(page[1], new URL(fontDescriptorFile, document.baseURI)).href).then(image => [image, descriptor]) :
+			Promise.reject('no page');
+	})
+	.catch(() => loadImage(builtinFontImageFile).then(image => [image, null]));
+
 function loadResourcesThenRun() {
 	Promise.all([
 		loadImage('tiles.png'),
-		loadImage('font.png'),
+		loadFont(),
 		fetch('game.wasm'),
-	]).then(([tileImage, fontImage, wasm]) => {
-		main([tileImage, fontImage], wasm);
+	]).then(([tileImage, [fontImage, fontDescriptor], wasm]) => {
+		main([tileImage, fontImage], fontDescriptor, wasm);
 	});
 }
 
-function main(textureImages, wasm) {
+function main(textureImages, fontDescriptor, wasm) {
 
 	// The projection matrix mostly stays as zeroes
 
@@ -59,7 +82,7 @@ function main(textureImages, wasm) {
 
 	// Instantiate and run the WebAssembly module.
 
-	runWasm(gl, glResources, wasm);
+	runWasm(gl, glResources, fontDescriptor, wasm);
 }
 
 // Convert Javascript's key names into numeric codes for use in the Rust program
@@ -145,32 +168,58 @@ const keymap = {
 	Quote: 222,
 };
 
-function runWasm(gl, glResources, wasm) {
+function runWasm(gl, glResources, fontDescriptor, wasm) {
 
 	let screenValid = false;
 
 	let importObject = {
 		env: {
-			js_draw_rect: function(dst_x, dst_y, size_x, size_y, color) {
-				drawTile(gl, glResources, dst_x, dst_y, size_x, size_y, color, glResources.textures.length - 1, 0, 0);
+			js_draw_quads: function(ptr, len) {
+				const words = new Int32Array(wasmMemory.buffer, ptr, len * quadWords);
+				const uwords = new Uint32Array(wasmMemory.buffer, ptr, len * quadWords);
+				for (let i = 0; i < len * quadWords; i += quadWords) {
+					drawTile(gl, glResources, words[i], words[i+1], words[i+2], words[i+3], uwords[i+4], uwords[i+5], words[i+6], words[i+7], words[i+8], words[i+9]);
+				}
 			},
-			js_draw_tile: function(dst_x, dst_y, size_x, size_y, color, textureIndex, src_x, src_y) {
-				drawTile(gl, glResources, dst_x, dst_y, size_x, size_y, color, textureIndex, src_x, src_y);
+			js_draw_map_layer: function(ptr, len, dirtyPtr, dirtyLen, rebuild, offsetX, offsetY, scale) {
+				const quads = new Int32Array(wasmMemory.buffer, ptr, len * quadWords);
+				const dirty = new Uint32Array(wasmMemory.buffer, dirtyPtr, dirtyLen);
+				drawMapLayer(gl, glResources, quads, dirty, rebuild != 0, offsetX, offsetY, scale);
 			},
 			js_invalidate_screen: function() {
 				screenValid = false;
-			}
+			},
+			js_play_sound: function(id, volume, pan) {
+				playSound(id, volume, pan);
+			},
+			js_save_key_bindings: function(preset, actionCount, ptr, len) {
+				const keyActionPairs = new Int32Array(wasmMemory.buffer, ptr, len);
+				const keyBindings = {
+					version: keyBindingsVersion,
+					preset: preset,
+					actionCount: actionCount,
+					keyActionPairs: Array.from(keyActionPairs),
+				};
+				try {
+					localStorage.setItem('keyBindings', JSON.stringify(keyBindings));
+				} catch (e) {
+					// Storage may be disabled; the bindings just won't persist.
+				}
+			},
 		},
 	};
 
+	let wasmMemory = null;
+
 	wasm.arrayBuffer().then(bytes =>
 		WebAssembly.instantiate(bytes, importObject)
 	).then(result => {
 		const wasmExports = result.instance.exports;
+		wasmMemory = wasmExports.memory;
 
 		function ensureScreenValid() {
 			if (!screenValid) {
-				drawScreen(gl, glResources, wasmExports.rs_on_draw);
+				drawScreen(gl, glResources, wasmExports.rs_on_resize, wasmExports.rs_on_draw);
 				screenValid = true;
 			}
 		}
@@ -179,8 +228,33 @@ function runWasm(gl, glResources, wasm) {
 		const seed1 = Math.random() * 2**32;
 
 		wasmExports.rs_start(seed0, seed1);
+
+		if (fontDescriptor !== null && !loadFontDescriptor(wasmExports, fontDescriptor)) {
+			// The game kept its built-in font, so put that font's image back.
+			console.warn('Unable to load ' + fontDescriptorFile + '; using the built-in font');
+			loadImage(builtinFontImageFile).then(image => {
+				setTextureImage(gl, glResources, 1, image);
+				screenValid = false;
+				ensureScreenValid();
+			});
+		}
+
+		loadKeyBindings(wasmExports);
+		initLanguage(wasmExports);
 		ensureScreenValid();
 
+		document.getElementById('language').addEventListener('change', e => {
+			setLanguage(wasmExports, e.target.value);
+			e.target.blur(); // so game keys don't also change the selection
+			ensureScreenValid();
+		});
+
+		// Browsers only allow audio to start in response to user input.
+
+		document.body.addEventListener('keydown', startAudio);
+		gl.canvas.addEventListener('mousedown', startAudio);
+		gl.canvas.addEventListener('touchstart', startAudio);
+
 		document.body.addEventListener('keydown', e => {
 			const key = keymap[e.code] || null;
 			// console.log("Key Pressed:" + e.key + " (" + e.code + ") -> " + key);
@@ -190,9 +264,236 @@ function runWasm(gl, glResources, wasm) {
 				ensureScreenValid();
 			}
 		});
+
+		// Mouse and touch positions are passed with the origin at the lower left, to match drawing.
+
+		const canvas = gl.canvas;
+
+		function canvasPos(clientX, clientY) {
+			const rect = canvas.getBoundingClientRect();
+			return [Math.floor(clientX - rect.left), Math.floor(rect.bottom - clientY)];
+		}
+
+		canvas.addEventListener('mousemove', e => {
+			const [x, y] = canvasPos(e.clientX, e.clientY);
+			wasmExports.rs_on_mouse_move(x, y, canvas.clientWidth, canvas.clientHeight);
+			ensureScreenValid();
+		});
+
+		canvas.addEventListener('mouseleave', e => {
+			wasmExports.rs_on_mouse_leave();
+			ensureScreenValid();
+		});
+
+		canvas.addEventListener('mousedown', e => {
+			e.preventDefault();
+			const [x, y] = canvasPos(e.clientX, e.clientY);
+			wasmExports.rs_on_mouse_down(x, y, canvas.clientWidth, canvas.clientHeight, e.button, e.shiftKey);
+			ensureScreenValid();
+		});
+
+		canvas.addEventListener('contextmenu', e => e.preventDefault());
+
+		// Calling preventDefault() on touch events keeps the browser from also sending mouse events.
+
+		function onTouch(e, handler) {
+			e.preventDefault();
+			const touch = e.changedTouches[0];
+			const [x, y] = canvasPos(touch.clientX, touch.clientY);
+			handler(x, y, canvas.clientWidth, canvas.clientHeight);
+			ensureScreenValid();
+		}
+
+		canvas.addEventListener('touchstart', e => onTouch(e, wasmExports.rs_on_touch_start), { passive: false });
+		canvas.addEventListener('touchmove', e => onTouch(e, wasmExports.rs_on_touch_move), { passive: false });
+		canvas.addEventListener('touchend', e => onTouch(e, wasmExports.rs_on_touch_end), { passive: false });
+
+		// Animations advance once per frame; the game asks for a redraw while any are playing.
+
+		let timePrev = null;
+
+		function onFrame(time) {
+			const dt = (timePrev === null) ? 0 : time - timePrev;
+			timePrev = time;
+			wasmExports.rs_on_tick(dt);
+			ensureScreenValid();
+			requestAnimationFrame(onFrame);
+		}
+
+		requestAnimationFrame(onFrame);
 	});
 }
 
+// Sound effects, synthesized rather than loaded. Indexed by the sound IDs in
+// audio.rs. Noise sounds are filtered white noise; tone sounds sweep an oscillator
+// from one frequency to another; blips are a few short tones, like speech.
+
+const sounds = [
+	{ noise: 500, duration: 0.05, gain: 0.15 }, // footstep
+	{ tone: 'sawtooth', freqs: [190, 120], duration: 0.25, gain: 0.08 }, // creak
+	{ noise: 1400, duration: 0.3, gain: 0.25 }, // splash
+	{ tone: 'square', freqs: [220, 330], duration: 0.3, gain: 0.08 }, // shout
+	{ noise: 180, duration: 0.15, gain: 0.5 }, // damage
+	{ tone: 'sine', freqs: [1300, 1900], duration: 0.15, gain: 0.2 }, // coin
+	{ noise: 2800, duration: 0.2, gain: 0.12 }, // outfit swap
+	{ blips: 3, freq: 150, gain: 0.05 }, // outer guard voice
+	{ blips: 3, freq: 230, gain: 0.05 }, // inner guard voice
+];
+
+let audioContext = null;
+let noiseBuffer = null;
+
+function startAudio() {
+	if (audioContext === null) {
+		const AudioContext = window.AudioContext || window.webkitAudioContext;
+		if (!AudioContext) {
+			return;
+		}
+		audioContext = new AudioContext();
+		noiseBuffer = audioContext.createBuffer(1, audioContext.sampleRate, audioContext.sampleRate);
+		const samples = noiseBuffer.getChannelData(0);
+		for (let i = 0; i < samples.length; ++i) {
+			samples[i] = Math.random() * 2 - 1;
+		}
+	}
+	if (audioContext.state === 'suspended') {
+		audioContext.resume();
+	}
+}
+
+function playSound(id, volume, pan) {
+	const sound = sounds[id];
+	if (audioContext === null || sound === undefined) {
+		return;
+	}
+
+	const t = audioContext.currentTime;
+
+	const output = audioContext.createGain();
+	output.gain.value = sound.gain * volume;
+	let destination = output;
+	if (audioContext.createStereoPanner) {
+		const panner = audioContext.createStereoPanner();
+		panner.pan.value = pan;
+		output.connect(panner);
+		destination = panner;
+	}
+	destination.connect(audioContext.destination);
+
+	function envelope(start, duration) {
+		const gain = audioContext.createGain();
+		gain.gain.setValueAtTime(1, start);
+		gain.gain.exponentialRampToValueAtTime(0.001, start + duration);
+		gain.connect(output);
+		return gain;
+	}
+
+	if (sound.noise !== undefined) {
+		const source = audioContext.createBufferSource();
+		source.buffer = noiseBuffer;
+		const filter = audioContext.createBiquadFilter();
+		filter.type = 'bandpass';
+		filter.frequency.value = sound.noise;
+		source.connect(filter);
+		filter.connect(envelope(t, sound.duration));
+		source.start(t);
+		source.stop(t + sound.duration);
+	} else if (sound.tone !== undefined) {
+		const osc = audioContext.createOscillator();
+		osc.type = sound.tone;
+		osc.frequency.setValueAtTime(sound.freqs[0], t);
+		osc.frequency.exponentialRampToValueAtTime(sound.freqs[1], t + sound.duration);
+		osc.connect(envelope(t, sound.duration));
+		osc.start(t);
+		osc.stop(t + sound.duration);
+	} else {
+		for (let i = 0; i < sound.blips; ++i) {
+			const start = t + i * 0.09;
+			const osc = audioContext.createOscillator();
+			osc.type = 'square';
+			osc.frequency.value = sound.freq * (0.8 + Math.random() * 0.4);
+			osc.connect(envelope(start, 0.06));
+			osc.start(start);
+			osc.stop(start + 0.06);
+		}
+	}
+}
+
+// Language codes, in the order of the language indices passed to rs_set_language()
+
+const languageCodes = ['en', 'fr', 'es'];
+
+// Use the language from the page's ?lang= parameter, or the one chosen last time,
+// or else the first of the browser's preferred languages that the game supports.
+
+function initLanguage(wasmExports) {
+	let code = new URLSearchParams(window.location.search).get('lang');
+
+	if (!languageCodes.includes(code)) {
+		try {
+			code = localStorage.getItem('language');
+		} catch (e) {
+			code = null;
+		}
+	}
+
+	if (!languageCodes.includes(code)) {
+		code = navigator.languages.map(lang => lang.slice(0, 2).toLowerCase()).find(lang => languageCodes.includes(lang)) || 'en';
+	}
+
+	document.getElementById('language').value = code;
+	setLanguage(wasmExports, code);
+}
+
+function setLanguage(wasmExports, code) {
+	const index = languageCodes.indexOf(code);
+	if (index < 0) {
+		return;
+	}
+
+	wasmExports.rs_set_language(index);
+
+	try {
+		localStorage.setItem('language', code);
+	} catch (e) {
+		// Storage may be disabled; the choice just won't persist.
+	}
+}
+
+// Pass the descriptor's text to the game. Returns whether the game could use it.
+
+function loadFontDescriptor(wasmExports, descriptor) {
+	const bytes = new TextEncoder().encode(descriptor);
+	const ptr = wasmExports.rs_alloc(bytes.length);
+	new Uint8Array(wasmExports.memory.buffer, ptr, bytes.length).set(bytes);
+	return wasmExports.rs_load_font(ptr, bytes.length) != 0;
+}
+
+// Saved key bindings are layered over the defaults of the preset they started
+// from. Actions added to the game since they were saved keep their default keys.
+// Bump the version if the saved layout changes; older data is then ignored.
+
+const keyBindingsVersion = 1;
+
+function loadKeyBindings(wasmExports) {
+	let keyBindings = null;
+	try {
+		keyBindings = JSON.parse(localStorage.getItem('keyBindings'));
+	} catch (e) {
+		return;
+	}
+
+	if (keyBindings === null || keyBindings.version !== keyBindingsVersion || !Array.isArray(keyBindings.keyActionPairs)) {
+		return;
+	}
+
+	const keyActionPairs = keyBindings.keyActionPairs;
+	wasmExports.rs_reset_key_bindings(keyBindings.preset, keyBindings.actionCount);
+	for (let i = 0; i + 1 < keyActionPairs.length; i += 2) {
+		wasmExports.rs_bind_key(keyActionPairs[i], keyActionPairs[i + 1]);
+	}
+}
+
 function initGlResources(gl, textureImages) {
 	const vsSource = `
 		attribute vec4 aVertexPosition;
@@ -228,6 +529,9 @@ function initGlResources(gl, textureImages) {
 	const textures = textureImages.map(image => createTextureFromImage(gl, image));
 	textures.push(createTextureFromColor(gl, 255, 255, 255, 255));
 
+	const textureSizes = textureImages.map(image => [image.width, image.height]);
+	textureSizes.push([1, 1]);
+
 	const glResources = {
 		program: program,
 		attribLocations: {
@@ -240,7 +544,17 @@ function initGlResources(gl, textureImages) {
 		},
 		buffers: buffers,
 		textures: textures,
+		textureSizes: textureSizes,
 		currentTextureIndex: 0,
+		mapLayer: {
+			numQuads: 0,
+			vertexPositions: new Float32Array(0),
+			vertexColors: new Uint32Array(0),
+			buffers: {
+				position: gl.createBuffer(),
+				color: gl.createBuffer(),
+			},
+		},
 	};
 
 	gl.blendFunc(gl.SRC_ALPHA, gl.ONE_MINUS_SRC_ALPHA);
@@ -296,7 +610,7 @@ function createElementBuffer(gl) {
 	return indexBuffer;
 }
 
-function drawScreen(gl, glResources, drawFunc) {
+function drawScreen(gl, glResources, resizeFunc, drawFunc) {
 	resizeCanvasToDisplaySize(gl.canvas);
 	const screenX = gl.canvas.clientWidth;
 	const screenY = gl.canvas.clientHeight;
@@ -308,7 +622,8 @@ function drawScreen(gl, glResources, drawFunc) {
 	gl.clear(gl.COLOR_BUFFER_BIT);
 
 	gl.uniformMatrix4fv(glResources.uniformLocations.projectionMatrix, false, projectionMatrix);
-	
+
+	resizeFunc(screenX, screenY);
 	drawFunc(screenX, screenY);
 
 	renderQuads(gl, glResources);
@@ -373,6 +688,15 @@ function createTextureFromColor(gl, r, g, b, a) {
 	return texture;
 }
 
+// Replace a texture between frames
+
+function setTextureImage(gl, glResources, textureIndex, image) {
+	gl.deleteTexture(glResources.textures[textureIndex]);
+	glResources.textures[textureIndex] = createTextureFromImage(gl, image);
+	glResources.textureSizes[textureIndex] = [image.width, image.height];
+	gl.bindTexture(gl.TEXTURE_2D, glResources.textures[glResources.currentTextureIndex]);
+}
+
 function createTextureFromImage(gl, image) {
 	const texture = gl.createTexture();
 	gl.bindTexture(gl.TEXTURE_2D, texture);
@@ -404,21 +728,93 @@ function renderQuads(gl, glResources) {
 	numQuads = 0;
 }
 
-function drawTile(gl, glResources, destX, destY, sizeX, sizeY, color, textureIndex, srcX, srcY) {
+function drawTile(gl, glResources, destX, destY, sizeX, sizeY, color, textureIndex, srcX, srcY, srcSizeX, srcSizeY) {
 	const x0 = destX;
 	const y0 = destY;
 	const x1 = destX + sizeX;
 	const y1 = destY + sizeY;
-	const texSizeX = 256; // hard-coded; should change
-	const texSizeY = 256; // hard-coded; should change
+	const [texSizeX, texSizeY] = glResources.textureSizes[Math.max(0, Math.min(glResources.textureSizes.length - 1, textureIndex))];
 	// Note: Flipping vertically here by swapping the expected values for t0 and t1
 	const s0 = srcX / texSizeX;
-	const t0 = (srcY + sizeY) / texSizeY;
-	const s1 = (srcX + sizeX) / texSizeX;
+	const t0 = (srcY + srcSizeY) / texSizeY;
+	const s1 = (srcX + srcSizeX) / texSizeX;
 	const t1 = srcY / texSizeY;
 	addQuad(gl, glResources, x0, y0, x1, y1, s0, t0, s1, t1, color, textureIndex);
 }
 
+// The map layer is kept in its own vertex buffers between frames, and only the
+// quads that changed are rewritten. It's drawn with the tile texture, scaled
+// and offset by the projection matrix.
+
+function drawMapLayer(gl, glResources, quads, dirty, rebuild, offsetX, offsetY, scale) {
+	renderQuads(gl, glResources);
+
+	const mapLayer = glResources.mapLayer;
+	const numLayerQuads = quads.length / quadWords;
+	const [texSizeX, texSizeY] = glResources.textureSizes[0];
+
+	function writeLayerQuad(i) {
+		const j = i * quadWords;
+		const [destX, destY, sizeX, sizeY, color, _, srcX, srcY, srcSizeX, srcSizeY] = quads.subarray(j, j + quadWords);
+		writeQuad(mapLayer.vertexPositions, mapLayer.vertexColors, i,
+			destX, destY, destX + sizeX, destY + sizeY,
+			srcX / texSizeX, (srcY + srcSizeY) / texSizeY, (srcX + srcSizeX) / texSizeX, srcY / texSizeY,
+			color >>> 0);
+	}
+
+	if (rebuild || numLayerQuads != mapLayer.numQuads) {
+		mapLayer.numQuads = numLayerQuads;
+		mapLayer.vertexPositions = new Float32Array(numLayerQuads * 16);
+		mapLayer.vertexColors = new Uint32Array(numLayerQuads * 4);
+		for (let i = 0; i < numLayerQuads; ++i) {
+			writeLayerQuad(i);
+		}
+		gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.position);
+		gl.bufferData(gl.ARRAY_BUFFER, mapLayer.vertexPositions, gl.STATIC_DRAW);
+		gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.color);
+		gl.bufferData(gl.ARRAY_BUFFER, mapLayer.vertexColors, gl.STATIC_DRAW);
+	} else {
+		for (const i of dirty) {
+			writeLayerQuad(i);
+			gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.position);
+			gl.bufferSubData(gl.ARRAY_BUFFER, i * 64, mapLayer.vertexPositions.subarray(i * 16, (i + 1) * 16));
+			gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.color);
+			gl.bufferSubData(gl.ARRAY_BUFFER, i * 16, mapLayer.vertexColors.subarray(i * 4, (i + 1) * 4));
+		}
+	}
+
+	// Scale and offset the layer, on top of the usual pixels-to-clip-space projection
+
+	const layerMatrix = projectionMatrix.slice();
+	layerMatrix[0] *= scale;
+	layerMatrix[5] *= scale;
+	layerMatrix[12] += offsetX * projectionMatrix[0];
+	layerMatrix[13] += offsetY * projectionMatrix[5];
+	gl.uniformMatrix4fv(glResources.uniformLocations.projectionMatrix, false, layerMatrix);
+
+	gl.bindTexture(gl.TEXTURE_2D, glResources.textures[0]);
+
+	// The index buffer covers maxQuads quads, so draw in batches of that many
+
+	for (let i = 0; i < mapLayer.numQuads; i += maxQuads) {
+		const n = Math.min(maxQuads, mapLayer.numQuads - i);
+		gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.position);
+		gl.vertexAttribPointer(glResources.attribLocations.vertexPosition, 4, gl.FLOAT, false, 0, i * 64);
+		gl.bindBuffer(gl.ARRAY_BUFFER, mapLayer.buffers.color);
+		gl.vertexAttribPointer(glResources.attribLocations.vertexColor, 4, gl.UNSIGNED_BYTE, true, 0, i * 16);
+		gl.drawElements(gl.TRIANGLES, 6 * n, gl.UNSIGNED_SHORT, 0);
+	}
+
+	// Restore the state for drawing the per-frame quads
+
+	gl.bindBuffer(gl.ARRAY_BUFFER, glResources.buffers.position);
+	gl.vertexAttribPointer(glResources.attribLocations.vertexPosition, 4, gl.FLOAT, false, 0, 0);
+	gl.bindBuffer(gl.ARRAY_BUFFER, glResources.buffers.color);
+	gl.vertexAttribPointer(glResources.attribLocations.vertexColor, 4, gl.UNSIGNED_BYTE, true, 0, 0);
+	gl.uniformMatrix4fv(glResources.uniformLocations.projectionMatrix, false, projectionMatrix);
+	gl.bindTexture(gl.TEXTURE_2D, glResources.textures[glResources.currentTextureIndex]);
+}
+
 function addQuad(gl, glResources, x0, y0, x1, y1, s0, t0, s1, t1, color, textureIndex) {
 	textureIndex = Math.max(0, Math.min(glResources.textures.length - 1, textureIndex));
 	if (textureIndex != glResources.currentTextureIndex) {
@@ -431,9 +827,15 @@ function addQuad(gl, glResources, x0, y0, x1, y1, s0, t0, s1, t1, color, texture
 		renderQuads(gl, glResources);
 	}
 
-	// Append four vertices to the position/texcoord and color arrays
+	writeQuad(vertexPositions, vertexColors, numQuads, x0, y0, x1, y1, s0, t0, s1, t1, color);
+
+	++numQuads;
+}
 
-	const i = numQuads * 16;
+// Write quad i's four vertices to position/texcoord and color arrays
+
+function writeQuad(vertexPositions, vertexColors, quadIndex, x0, y0, x1, y1, s0, t0, s1, t1, color) {
+	const i = quadIndex * 16;
 
 	vertexPositions[i+0] = x0;
 	vertexPositions[i+1] = y0;
@@ -455,9 +857,7 @@ function addQuad(gl, glResources, x0, y0, x1, y1, s0, t0, s1, t1, color, texture
 	vertexPositions[i+14] = s1;
 	vertexPositions[i+15] = t1;
 
-	for (let j = numQuads * 4; j < (numQuads + 1) * 4; ++j) {
+	for (let j = quadIndex * 4; j < (quadIndex + 1) * 4; ++j) {
 		vertexColors[j] = color;
 	}
-
-	++numQuads;
 }